@@ -0,0 +1,244 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Datapack, GristCostRecipe, Ingredient, ItemTags, Recipe};
+
+/// How far off something has to be to be reported by [`balance_report`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BalanceThresholds {
+    /// How much a cost can change from the reference, as a fraction of the reference cost
+    pub change: f64,
+    /// How many times cheaper or pricier than the median of its tag an item can be
+    pub tag_factor: f64,
+    /// Tags with fewer priced items than this aren't compared
+    pub min_tag_size: usize,
+    /// How many times more or less of a grist type a recipe can use than the median of recipes using it
+    pub grist_factor: f64,
+}
+
+impl Default for BalanceThresholds {
+    fn default() -> Self {
+        Self {
+            change: 0.5,
+            tag_factor: 3.0,
+            min_tag_size: 3,
+            grist_factor: 3.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FindingKind {
+    /// The cost is different from the reference pack's
+    Changed,
+    /// The item is much cheaper or pricier than the other items in a tag
+    TagOutlier(String),
+    /// The recipe uses much more or less of a grist type than others using it
+    GristOutlier(String),
+}
+
+/// Something that looks unbalanced, with a score for how far off it is
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceFinding {
+    pub kind: FindingKind,
+    /// The ingredient of the grist cost, in csv form
+    pub item: String,
+    pub location: String,
+    /// How many times bigger the larger of the two things compared is, so always at least 1
+    pub score: f64,
+    pub detail: String,
+}
+
+/// What a balance report can be sorted by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BalanceSort {
+    /// Furthest off first
+    #[default]
+    Score,
+    Item,
+    Kind,
+}
+
+impl FromStr for BalanceSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "score" => Ok(BalanceSort::Score),
+            "item" => Ok(BalanceSort::Item),
+            "kind" => Ok(BalanceSort::Kind),
+            _ => Err(format!("Unknown sort \"{s}\", expected score, item or kind")),
+        }
+    }
+}
+
+fn total(recipe: &GristCostRecipe) -> i64 {
+    recipe.grist_cost.values().map(|amount| *amount as i64).sum()
+}
+
+/// How many times bigger the larger is, infinite if only one is zero
+fn ratio(a: f64, b: f64) -> f64 {
+    let (small, large) = if a < b { (a, b) } else { (b, a) };
+    if small <= 0.0 {
+        if large <= 0.0 {
+            1.0
+        } else {
+            f64::INFINITY
+        }
+    } else {
+        large / small
+    }
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let middle = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[middle - 1] + values[middle]) / 2.0
+    } else {
+        values[middle]
+    }
+}
+
+/// Compares every grist cost in a pack to a reference pack, the items in the same tags, and the amounts of each
+/// grist type other recipes use, sorted furthest off first
+///
+/// Costs are compared by the total amount of grist, and recipes are matched to the reference by ingredient since
+/// they may be named differently
+pub fn balance_report(
+    datapack: &Datapack,
+    reference: Option<&Datapack>,
+    tags: &ItemTags,
+    thresholds: &BalanceThresholds,
+) -> Vec<BalanceFinding> {
+    let costs: Vec<(&str, &GristCostRecipe)> = datapack
+        .recipes
+        .iter()
+        .filter_map(|(location, recipe)| match recipe {
+            Recipe::GristCost(recipe) => Some((location.as_str(), recipe)),
+            Recipe::Combination(_) => None,
+        })
+        .collect();
+    let mut findings = vec![];
+
+    if let Some(reference) = reference {
+        let reference_costs: BTreeMap<&Ingredient, &GristCostRecipe> = reference
+            .recipes
+            .values()
+            .filter_map(|recipe| match recipe {
+                Recipe::GristCost(recipe) => Some((&recipe.ingredient, recipe)),
+                Recipe::Combination(_) => None,
+            })
+            .collect();
+        for (location, recipe) in &costs {
+            let Some(old) = reference_costs.get(&recipe.ingredient) else {
+                continue;
+            };
+            let (old_total, new_total) = (total(old) as f64, total(recipe) as f64);
+            let change = (new_total - old_total).abs() / old_total.max(1.0);
+            if change > thresholds.change {
+                findings.push(BalanceFinding {
+                    kind: FindingKind::Changed,
+                    item: recipe.ingredient.to_string(),
+                    location: location.to_string(),
+                    score: ratio(old_total, new_total),
+                    detail: format!("total grist went from {old_total} to {new_total}"),
+                });
+            }
+        }
+    }
+
+    let item_costs: BTreeMap<&str, (&str, f64)> = costs
+        .iter()
+        .filter_map(|(location, recipe)| match &recipe.ingredient {
+            Ingredient::Item(item) => Some((item.as_str(), (*location, total(recipe) as f64))),
+            _ => None,
+        })
+        .collect();
+    for tag in tags.ids() {
+        let Ok(items) = tags.resolve(tag) else {
+            continue;
+        };
+        let priced: Vec<(&str, &str, f64)> = items
+            .iter()
+            .filter_map(|item| item_costs.get_key_value(item.as_str()))
+            .map(|(item, (location, total))| (*item, *location, *total))
+            .collect();
+        if priced.len() < thresholds.min_tag_size.max(2) {
+            continue;
+        }
+        let median = median(priced.iter().map(|(_, _, total)| *total).collect());
+        for (item, location, total) in priced {
+            let score = ratio(total, median);
+            if score > thresholds.tag_factor {
+                let relation = if total < median { "cheaper" } else { "pricier" };
+                findings.push(BalanceFinding {
+                    kind: FindingKind::TagOutlier(tag.to_string()),
+                    item: item.to_string(),
+                    location: location.to_string(),
+                    score,
+                    detail: format!("{total} total grist is {score:.1} times {relation} than the median {median} of #{tag}"),
+                });
+            }
+        }
+    }
+
+    let mut amounts: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+    for (_, recipe) in &costs {
+        for (grist, amount) in &recipe.grist_cost {
+            amounts.entry(grist).or_default().push(*amount as f64);
+        }
+    }
+    let medians: BTreeMap<&str, f64> = amounts
+        .into_iter()
+        .filter(|(_, amounts)| amounts.len() >= 2)
+        .map(|(grist, amounts)| (grist, median(amounts)))
+        .collect();
+    for (location, recipe) in &costs {
+        for (grist, amount) in &recipe.grist_cost {
+            let Some(median) = medians.get(grist.as_str()) else {
+                continue;
+            };
+            let score = ratio(*amount as f64, *median);
+            if score > thresholds.grist_factor {
+                let relation = if (*amount as f64) < *median { "less" } else { "more" };
+                findings.push(BalanceFinding {
+                    kind: FindingKind::GristOutlier(grist.clone()),
+                    item: recipe.ingredient.to_string(),
+                    location: location.to_string(),
+                    score,
+                    detail: format!("{amount} {grist} is {score:.1} times {relation} than the median {median}"),
+                });
+            }
+        }
+    }
+
+    sort_findings(&mut findings, BalanceSort::Score);
+    findings
+}
+
+pub fn sort_findings(findings: &mut [BalanceFinding], sort: BalanceSort) {
+    match sort {
+        BalanceSort::Score => findings.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.item.cmp(&b.item))),
+        BalanceSort::Item => findings.sort_by(|a, b| a.item.cmp(&b.item).then_with(|| a.kind.cmp(&b.kind))),
+        BalanceSort::Kind => findings.sort_by(|a, b| a.kind.cmp(&b.kind).then_with(|| b.score.total_cmp(&a.score))),
+    }
+}
+
+impl Display for FindingKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FindingKind::Changed => write!(f, "changed"),
+            FindingKind::TagOutlier(tag) => write!(f, "outlier in #{tag}"),
+            FindingKind::GristOutlier(grist) => write!(f, "unusual amount of {grist}"),
+        }
+    }
+}
+
+impl Display for BalanceFinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:>6.1}  {}, {}: {} ({})", self.score, self.item, self.kind, self.detail, self.location)
+    }
+}
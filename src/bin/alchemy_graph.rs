@@ -0,0 +1,87 @@
+use std::path::Path;
+
+use minestuck_datapack_generator::{
+    grist_total, AlchemyEdge, Config, Datapack, DiagramFilter, DiagramFormat, CONFIG_FILE,
+};
+
+const USAGE: &str = "Usage: alchemy_graph [check | recipes <item> | uses <item> | costs [--differ] | exploits \
+                     | export <dot|graphml|mermaid> [--root <item>] [--namespace <namespace>] [--depth <depth>]]";
+
+fn print_edge(edge: &AlchemyEdge) {
+    println!(
+        "{} {} {} -> {} ({} in {})",
+        edge.inputs[0],
+        edge.mode().symbol(),
+        edge.inputs[1],
+        edge.output,
+        edge.location,
+        edge.pack
+    );
+}
+
+fn main() {
+    let config = Config::load(CONFIG_FILE);
+    let datapack = if Path::new("data").is_dir() {
+        Datapack::load(".")
+    } else {
+        Datapack::new()
+    };
+    let graph = config.alchemy_graph(&config.load_baselines(), &datapack, "this datapack");
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] | ["check"] => {
+            for cycle in graph.cycles() {
+                println!("Combinations form a cycle between {}", cycle.join(", "));
+            }
+            for item in graph.without_grist_cost() {
+                println!("{item} has no grist cost, so it can't be alchemized");
+            }
+            for item in graph.unobtainable() {
+                println!("{item} can only be made from items that can't be had");
+            }
+        }
+        ["recipes", item] => graph.recipes_for(item).into_iter().for_each(print_edge),
+        ["uses", item] => graph.uses_of(item).into_iter().for_each(print_edge),
+        ["costs", ref options @ ..] if options.iter().all(|option| *option == "--differ") => {
+            let only_different = !options.is_empty();
+            let rollup = graph.roll_up_costs(&config.load_crafting_recipes(), &config.estimate);
+            for comparison in rollup.comparisons(&graph) {
+                let different = match (&comparison.explicit, &comparison.derived) {
+                    (Some((explicit, _)), Some(derived)) => grist_total(explicit) != grist_total(&derived.grist),
+                    _ => false,
+                };
+                if different || !only_different {
+                    println!("{comparison}");
+                }
+            }
+        }
+        ["exploits"] => {
+            for exploit in graph.exploits(&config.load_crafting_recipes()) {
+                println!("{exploit}");
+            }
+        }
+        ["export", format, ref options @ ..] => match export_options(format, options) {
+            Ok((format, filter)) => print!("{}", graph.diagram(format, &filter)),
+            Err(e) => eprintln!("{e}\n{USAGE}"),
+        },
+        _ => eprintln!("{USAGE}"),
+    }
+}
+
+fn export_options(format: &str, options: &[&str]) -> Result<(DiagramFormat, DiagramFilter), String> {
+    let format = format.parse()?;
+    let mut filter = DiagramFilter::default();
+    for option in options.chunks(2) {
+        match option {
+            ["--root", item] => filter.root = Some(item.to_string()),
+            ["--namespace", namespace] => filter.namespace = Some(namespace.to_string()),
+            ["--depth", depth] => filter.max_depth = Some(depth.parse().map_err(|e| format!("{e} in \"{depth}\""))?),
+            _ => return Err(format!("Unknown option {}", option.join(" "))),
+        }
+    }
+    if filter.max_depth.is_some() && filter.root.is_none() {
+        return Err("--depth needs a --root to count from".to_string());
+    }
+    Ok((format, filter))
+}
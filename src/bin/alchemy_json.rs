@@ -1,8 +1,9 @@
 use std::fs::File;
 use std::io::{stdin, BufRead, BufReader};
+use std::path::Path;
 use std::str::FromStr;
 
-use minestuck_datapack_generator::{Ingredient, Recipe, CombinationRecipe, CombinationMode, ResultItem};
+use minestuck_datapack_generator::{split_columns, Config, Datapack, Ingredient, Recipe, CombinationRecipe, CombinationMode, ResultItem, CONFIG_FILE};
 
 fn main() {
     let reader: Box<dyn BufRead> = if let Some(arg) = std::env::args().nth(1) {
@@ -12,13 +13,16 @@ fn main() {
         let stdin = stdin();
         Box::new(stdin.lock())
     };
-    let combination_dir = "data/minestuck/recipes/combination";
-    std::fs::create_dir_all(combination_dir).unwrap();
-    std::env::set_current_dir(combination_dir).unwrap();
+    let config = Config::load(CONFIG_FILE);
+    let mut datapack = Datapack::new();
+    //Tags already written here, such as by item_tag_json
+    if Path::new("data").is_dir() {
+        datapack.item_tags = Datapack::load(".").item_tags;
+    }
     for line in reader.lines() {
         let input = line.unwrap();
 
-        let columns: Vec<_> = input.split(',').map(str::trim).collect();
+        let columns = split_columns(&input);
         if columns.len() < 4 {
             //Silently ignore blank lines
             if !(columns.len() == 1 && columns[0].is_empty()) {
@@ -31,12 +35,21 @@ fn main() {
         let input2 = columns[2];
         let output = columns[3];
 
+        let (input1, input2) = match (Ingredient::parse(input1, config.loader), Ingredient::parse(input2, config.loader)) {
+            (Ok(input1), Ok(input2)) => (input1, input2),
+            (Err(e), _) | (_, Err(e)) => {
+                eprintln!("{e}");
+                continue;
+            }
+        };
+
         let recipe: Recipe = if let Ok(mode) = CombinationMode::from_str(mode) {
             CombinationRecipe {
-                input1: Ingredient::Item(input1.to_string()),
-                input2: Ingredient::Item(input2.to_string()),
+                input1,
+                input2,
                 mode,
-                output: ResultItem::Item(output.to_string())
+                output: ResultItem::Item(output.to_string()),
+                conditions: Default::default(),
             }.into()
         } else {
             eprintln!("Invalid mode {mode}");
@@ -44,12 +57,21 @@ fn main() {
         };
 
         if recipe.is_valid() {
-            let (mod_id, item_name) = output.split_once(':').unwrap();
-            std::fs::create_dir_all(mod_id).unwrap();
-            let file = File::create(&format!("{mod_id}/{item_name}.json")).unwrap();
-            serde_json::to_writer_pretty(file, &recipe).unwrap();
+            if let Err(e) = datapack.add(&config, recipe) {
+                eprintln!("{e}");
+            }
         } else {
             eprintln!("Invalid recipe: \"{recipe:#?}\"");
         }
     }
+    for unknown in datapack.unknown_items(&config.load_item_registry()) {
+        eprintln!("{unknown}");
+    }
+    //Without tag sources even vanilla tags are unknown
+    if !config.tag_sources.is_empty() {
+        for problem in datapack.tag_problems(&config.load_item_tags(&config.load_baselines(), &datapack)) {
+            eprintln!("{problem}");
+        }
+    }
+    datapack.write_recipes(".");
 }
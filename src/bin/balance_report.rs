@@ -0,0 +1,51 @@
+use std::path::Path;
+
+use minestuck_datapack_generator::{balance_report, sort_findings, BalanceSort, Config, Datapack, CONFIG_FILE};
+
+fn main() {
+    let config = Config::load(CONFIG_FILE);
+    let mut args = std::env::args().skip(1);
+    let mut reference_path = None;
+    let mut sort = BalanceSort::default();
+    while let Some(arg) = args.next() {
+        if arg == "--sort" {
+            match args.next().unwrap_or_default().parse() {
+                Ok(parsed) => sort = parsed,
+                Err(e) => {
+                    eprintln!("{e}");
+                    return;
+                }
+            }
+        } else {
+            reference_path = Some(arg);
+        }
+    }
+
+    let datapack = if Path::new("data").is_dir() {
+        Datapack::load(".")
+    } else {
+        Datapack::new()
+    };
+    let baselines = config.load_baselines();
+    //Without a reference pack, costs are compared to the baselines, later ones replacing earlier ones
+    let reference = match reference_path {
+        Some(path) => Some(Datapack::load(path)),
+        None if !baselines.is_empty() => {
+            let mut reference = Datapack::new();
+            for baseline in &baselines {
+                reference.recipes.extend(baseline.recipes.clone());
+            }
+            Some(reference)
+        }
+        None => None,
+    };
+    let tags = config.load_item_tags(&baselines, &datapack);
+    let mut findings = balance_report(&datapack, reference.as_ref(), &tags, &config.balance);
+    sort_findings(&mut findings, sort);
+    for finding in &findings {
+        println!("{finding}");
+    }
+    if findings.is_empty() {
+        println!("Nothing looks out of balance");
+    }
+}
@@ -0,0 +1,71 @@
+use std::{collections::BTreeSet, path::Path};
+
+use minestuck_datapack_generator::{
+    apply_cost_rules, load_cost_rules, Config, Datapack, GristRegistry, Recipe, CONFIG_FILE, GRIST_TYPE_FILE,
+};
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let preview = args.iter().any(|arg| arg == "--preview");
+    args.retain(|arg| arg != "--preview");
+    let Some(path) = args.first() else {
+        eprintln!("Give a rules file to generate costs from");
+        return;
+    };
+    let rules = match load_cost_rules(path) {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    let config = Config::load(CONFIG_FILE);
+    let datapack = if Path::new("data").is_dir() || Path::new(GRIST_TYPE_FILE).is_file() {
+        Datapack::load(".")
+    } else {
+        Datapack::new()
+    };
+    let baselines = config.load_baselines();
+    let tags = config.load_item_tags(&baselines, &datapack);
+    //Rules can match any item in the registries or in a tag
+    let registry = config.load_item_registry();
+    let mut items: BTreeSet<String> = registry.iter().map(str::to_string).collect();
+    for tag in tags.ids() {
+        items.extend(tags.resolve(tag).unwrap_or_default());
+    }
+    let recipes = baselines
+        .iter()
+        .chain([&datapack])
+        .flat_map(|datapack| datapack.recipes.values());
+    let outputs = match apply_cost_rules(&rules, items.iter().map(String::as_str), &tags, recipes) {
+        Ok(outputs) => outputs,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    let mut registry = GristRegistry::new(&config);
+    registry.add_datapack(&datapack);
+    for baseline in &baselines {
+        registry.add_datapack(baseline);
+    }
+    for output in outputs {
+        if preview {
+            print!("{}", output.preview());
+        } else {
+            eprintln!("{output}");
+            for problem in &output.problems {
+                eprintln!("  {problem}");
+            }
+        }
+        for recipe in output.recipes {
+            for unknown in registry.check(recipe.ingredient.id(), &recipe) {
+                eprintln!("{unknown}");
+            }
+            //Costs are printed in the form grist_json reads
+            if !preview {
+                println!("{}", Recipe::GristCost(recipe));
+            }
+        }
+    }
+}
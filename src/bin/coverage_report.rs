@@ -0,0 +1,38 @@
+use std::path::Path;
+
+use minestuck_datapack_generator::{Config, CoverageReport, Datapack, ItemRegistry, CONFIG_FILE};
+
+fn main() {
+    let config = Config::load(CONFIG_FILE);
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let summary = args.iter().any(|arg| arg == "--summary");
+    args.retain(|arg| arg != "--summary");
+    //Items come from the paths given, the same kinds of file as item_registries, or the config if there are none
+    let items = if args.is_empty() {
+        config.load_item_registry()
+    } else {
+        let mut items = ItemRegistry::new();
+        for path in &args {
+            if let Err(e) = items.load(path) {
+                eprintln!("Failed to read items from {path}, {e}");
+            }
+        }
+        items
+    };
+    if items.is_empty() {
+        eprintln!("No items to check, give a registry dump or mod jar, or add one to item_registries in {CONFIG_FILE}");
+        return;
+    }
+
+    let datapack = if Path::new("data").is_dir() {
+        Datapack::load(".")
+    } else {
+        Datapack::new()
+    };
+    let resolver = config.cost_resolver(&config.load_baselines(), &datapack, "this datapack");
+    let report = CoverageReport::new(&resolver, items.iter());
+    println!("{report}");
+    if !summary {
+        print!("{}", report.details());
+    }
+}
@@ -0,0 +1,29 @@
+use std::path::Path;
+
+use minestuck_datapack_generator::{estimate_costs, Config, Datapack, Recipe, CONFIG_FILE};
+
+fn main() {
+    //Drafts are printed in the form grist_json reads, so they can be reviewed and edited before adding them
+    let explain = std::env::args().skip(1).any(|arg| arg == "--explain");
+    let config = Config::load(CONFIG_FILE);
+    let datapack = if Path::new("data").is_dir() {
+        Datapack::load(".")
+    } else {
+        Datapack::new()
+    };
+    let resolver = config.cost_resolver(&config.load_baselines(), &datapack, "this datapack");
+    let recipes = config.load_crafting_recipes();
+    if recipes.is_empty() {
+        eprintln!("No recipes to estimate from, add jars or datapacks to crafting_sources in {CONFIG_FILE}");
+    }
+    for draft in estimate_costs(&resolver, &recipes, &config.estimate) {
+        if explain {
+            println!("{draft}");
+        } else {
+            match draft.recipe() {
+                Ok(recipe) => println!("{}", Recipe::GristCost(recipe)),
+                Err(e) => eprintln!("{e}, from {}", draft.source),
+            }
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use std::io::{stdin, BufRead};
+use std::path::Path;
+
+use minestuck_datapack_generator::{Config, Datapack, Ingredient, CONFIG_FILE};
+
+fn main() {
+    let config = Config::load(CONFIG_FILE);
+    let datapack = if Path::new("data").is_dir() {
+        Datapack::load(".")
+    } else {
+        Datapack::new()
+    };
+    let resolver = config.cost_resolver(&config.load_baselines(), &datapack, "this datapack");
+
+    //Items to explain are the arguments, or read from standard input one per line without any
+    let mut items: Vec<String> = std::env::args().skip(1).collect();
+    if items.is_empty() {
+        items = stdin().lock().lines().map(Result::unwrap).collect();
+    }
+    for item in items.iter().map(|item| item.trim()).filter(|item| !item.is_empty()) {
+        match Ingredient::parse(item, config.loader) {
+            Ok(ingredient @ (Ingredient::Item(_) | Ingredient::Nbt(_))) => println!("{}", resolver.resolve(&ingredient)),
+            Ok(_) => eprintln!("{item} isn't a single item"),
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+}
@@ -1,8 +1,11 @@
-use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{stdin, BufRead, BufReader};
+use std::path::Path;
 
-use minestuck_datapack_generator::{GristCostRecipe, Ingredient, Recipe};
+use minestuck_datapack_generator::{
+    evaluate_formulas, recipe_amounts, split_columns, split_outside_brackets, Config, CostFormula, Datapack, GristCostRecipe,
+    GristExpression, GristRegistry, Ingredient, Recipe, CONFIG_FILE, GENERATED_PRIORITY, GRIST_TYPE_FILE,
+};
 
 fn main() {
     let reader: Box<dyn BufRead> = if let Some(arg) = std::env::args().nth(1) {
@@ -12,58 +15,133 @@ fn main() {
         let stdin = stdin();
         Box::new(stdin.lock())
     };
-    let grist_cost_dir = "data/minestuck/recipes/grist_costs";
-    std::fs::create_dir_all(grist_cost_dir).unwrap();
-    std::env::set_current_dir(grist_cost_dir).unwrap();
+    let config = Config::load(CONFIG_FILE);
+    let mut datapack = Datapack::new();
+    //Grist types and tags already written here, such as by grist_type_json and item_tag_json
+    let existing = if Path::new("data").is_dir() || Path::new(GRIST_TYPE_FILE).is_file() {
+        Datapack::load(".")
+    } else {
+        Datapack::new()
+    };
+    datapack.grist_types = existing.grist_types.clone();
+    datapack.item_tags = existing.item_tags.clone();
+    let baselines = config.load_baselines();
+    let mut ingredients = vec![];
+    let mut formulas = vec![];
     for line in reader.lines() {
         let input = line.unwrap();
 
-        let mut columns = input.split(',');
-        let item = columns.next().unwrap().trim();
-        let costs: Result<BTreeMap<String, i32>, _> = columns
-            .map(|cost| {
-                if let Some((grist, amount)) = cost.split_once('=') {
-                    Ok((grist.to_string(), amount))
-                } else if let Some((grist, amount)) = cost.rsplit_once(':') {
-                    Ok((grist.to_string(), amount))
-                } else {
-                    Err(format!("Error: invalid grist cost format: \"{cost}\""))
-                }
-                .and_then(|(mut grist, amount)| {
-                    if !grist.contains(':') {
-                        grist = format!("minestuck:{grist}");
-                    }
-                    amount
-                        .trim()
-                        .parse::<i32>()
-                        .map(|amount| (grist.trim().to_string(), amount))
-                        .map_err(|e| format!("{e} in \"{amount}\""))
-                })
-            })
+        let mut columns = split_columns(&input).into_iter();
+        let item = columns.next().unwrap();
+        //A whole cost can be written after the item, like "minecraft:iron_block = 9 * minecraft:iron_ingot"
+        //NBT can have = in strings
+        let (item, whole_cost) = match split_outside_brackets(item, '=')[..] {
+            [item] => (item, None),
+            [item, expression] => (item.trim(), Some(expression)),
+            _ => {
+                eprintln!("Error: more than one = in \"{item}\"");
+                continue;
+            }
+        };
+        let mut priority = Ok(Some(GENERATED_PRIORITY));
+        let terms: Result<Vec<(Option<String>, GristExpression)>, _> = whole_cost
+            .map(|expression| expression.parse().map(|expression| (None, expression)))
+            .into_iter()
+            .chain(
+                columns
+                    .filter(|column| match column.strip_prefix("priority=").map(str::trim) {
+                        //Left out so Minestuck picks one from the ingredient
+                        Some("default") => {
+                            priority = Ok(None);
+                            false
+                        }
+                        Some(value) => {
+                            priority = value.parse().map(Some).map_err(|e| format!("{e} in priority \"{value}\""));
+                            false
+                        }
+                        None => true,
+                    })
+                    .map(|cost| {
+                        if let Some((grist, amount)) = cost.split_once('=') {
+                            Ok((grist, amount))
+                        } else if let Some((grist, amount)) = cost.rsplit_once(':') {
+                            Ok((grist, amount))
+                        } else {
+                            Err(format!("Error: invalid grist cost format: \"{cost}\""))
+                        }
+                        .and_then(|(grist, amount)| {
+                            //Amounts can be expressions, like "build=2 * minecraft:iron_ingot"
+                            amount
+                                .trim()
+                                .parse::<GristExpression>()
+                                .map(|amount| (Some(grist.trim().to_string()), amount))
+                        })
+                    }),
+            )
             .collect();
-        let costs = match costs {
-            Ok(costs) => costs,
+        let (terms, priority) = match terms.and_then(|terms| Ok((terms, priority?))) {
+            Ok(terms) => terms,
             Err(e) => {
                 eprintln!("{e}");
                 continue;
             }
         };
-        if costs.is_empty() {
+        if terms.is_empty() {
             continue;
         }
+        let ingredient = match Ingredient::parse(item, config.loader) {
+            Ok(ingredient) => ingredient,
+            Err(e) => {
+                eprintln!("{e}");
+                continue;
+            }
+        };
+        formulas.push((ingredient.to_string(), CostFormula { terms }));
+        ingredients.push((ingredient, priority));
+    }
+    //Expressions use the costs in this input first, then the ones already in this datapack and the baselines
+    let resolver = config.cost_resolver(&baselines, &existing, "this datapack");
+    let results = evaluate_formulas(&formulas, |item| resolver.item_cost(item).map(|(grist, _)| grist));
+    for (((ingredient, priority), (item, _)), result) in ingredients.into_iter().zip(&formulas).zip(results) {
+        //Amounts a recipe can't hold are reported rather than changed to one it can
+        let costs = match result.and_then(|costs| recipe_amounts(&costs)) {
+            Ok(costs) => costs,
+            Err(e) => {
+                eprintln!("{item}: {e}");
+                continue;
+            }
+        };
         let recipe: Recipe = GristCostRecipe {
-            priority: Some(101),
-            ingredient: Ingredient::Item(item.to_string()),
+            priority,
+            ingredient,
             grist_cost: costs,
+            conditions: Default::default(),
         }
         .into();
         if recipe.is_valid() {
-            let (mod_id, item_name) = item.split_once(':').unwrap();
-            std::fs::create_dir_all(mod_id).unwrap();
-            let file = File::create(&format!("{mod_id}/{item_name}.json")).unwrap();
-            serde_json::to_writer_pretty(file, &recipe).unwrap();
+            if let Err(e) = datapack.add(&config, recipe) {
+                eprintln!("{e}");
+            }
         } else {
             eprintln!("Invalid recipe: \"{recipe:#?}\"");
         }
     }
+    let mut registry = GristRegistry::new(&config);
+    registry.add_datapack(&datapack);
+    for baseline in &baselines {
+        registry.add_datapack(baseline);
+    }
+    for unknown in datapack.unknown_grist(&registry) {
+        eprintln!("{unknown}");
+    }
+    for unknown in datapack.unknown_items(&config.load_item_registry()) {
+        eprintln!("{unknown}");
+    }
+    //Without tag sources even vanilla tags are unknown
+    if !config.tag_sources.is_empty() {
+        for problem in datapack.tag_problems(&config.load_item_tags(&baselines, &datapack)) {
+            eprintln!("{problem}");
+        }
+    }
+    datapack.write_recipes(".");
 }
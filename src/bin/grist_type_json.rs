@@ -0,0 +1,54 @@
+use std::fs::File;
+use std::io::{stdin, BufRead, BufReader};
+
+use minestuck_datapack_generator::{parse_color, split_columns, validate_resource_location, Datapack, GristType};
+
+fn main() {
+    let reader: Box<dyn BufRead> = if let Some(arg) = std::env::args().nth(1) {
+        let file = File::open(arg).unwrap();
+        Box::new(BufReader::new(file))
+    } else {
+        let stdin = stdin();
+        Box::new(stdin.lock())
+    };
+    let mut datapack = Datapack::new();
+    for line in reader.lines() {
+        let input = line.unwrap();
+
+        let mut columns = split_columns(&input).into_iter();
+        let id = columns.next().unwrap();
+        if id.is_empty() {
+            continue;
+        }
+        if !validate_resource_location(id) {
+            eprintln!("Invalid grist type id {id}, it needs the id of the mod adding it");
+            continue;
+        }
+        let grist_type: Result<GristType, String> = columns.try_fold(GristType::default(), |mut grist_type, column| {
+            let (key, value) = column.split_once('=').unwrap_or((column, ""));
+            match key.trim() {
+                "color" => {
+                    grist_type.color = parse_color(value.trim()).ok_or_else(|| format!("Invalid color \"{value}\""))?
+                }
+                "value" => {
+                    grist_type.value = value.trim().parse().map_err(|e| format!("{e} in \"{value}\""))?
+                }
+                "candy" => grist_type.candy_item = Some(value.trim().to_string()),
+                "secondary" => grist_type.secondary = true,
+                "underling" => grist_type.underling = true,
+                _ => return Err(format!("Error: unknown grist type property \"{column}\"")),
+            }
+            Ok(grist_type)
+        });
+        match grist_type {
+            Ok(grist_type) if grist_type.is_valid() => {
+                if datapack.grist_types.insert(id.to_string(), grist_type).is_some() {
+                    eprintln!("Duplicate grist type {id}");
+                }
+            }
+            Ok(grist_type) => eprintln!("Invalid grist type: \"{grist_type:#?}\""),
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+    datapack.write_grist_types(".");
+}
@@ -1,10 +1,15 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::Value;
+
 use iced::{
     executor, theme,
     widget::{button, column, container, row, scrollable, text, text_input},
     Application, Color, Command, Element, Padding, Settings, Theme,
 };
 use minestuck_datapack_generator::{
-    grist_resource, validate_resource_location, Datapack, GristCostRecipe, Ingredient, Recipe, ResultItem,
+    default_priority, evaluate_formulas, recipe_amounts, grist_resource, load_conflicts, recipe_id, save_conflicts, ConflictPart, MergeSide, ThreeWayConflict, validate_resource_location, Conditions, Config, CostFormula, Datapack, CostResolver, GristCostRecipe, GristExpression, GristRegistry, GristType, Ingredient, ItemRegistry, ItemTags,
+    Loader, MCMeta, Recipe, RecipeIndex, RecipeQuery, Tag, CONFIG_FILE, GENERATED_PRIORITY,
 };
 
 fn main() -> iced::Result {
@@ -12,8 +17,249 @@ fn main() -> iced::Result {
 }
 
 struct DatapackGui {
+    config: Config,
+    grist_registry: GristRegistry,
+    item_checks: ItemChecks,
+    /// Parts of the loaded datapack that are written back unchanged on export
+    grist_types: BTreeMap<String, GristType>,
+    item_tags: BTreeMap<String, Tag>,
+    combinations: BTreeMap<String, Recipe>,
+    mcmeta: MCMeta,
     costs: Vec<CostEntry>,
+    /// Ids of recipes in the baseline packs, which can be hidden with a filter in pack.mcmeta
+    baseline: Vec<String>,
+    /// Grist costs in the baseline packs, to find ones that win over ours
+    resolver: CostResolver,
+    search: String,
+    /// The costs' recipes and the row each location is from, None when costs changed since it was built
+    search_index: Option<(RecipeIndex<'static>, BTreeMap<String, usize>)>,
+    /// Costs matching the search, None if there isn't one or it's invalid
+    search_results: Option<BTreeSet<usize>>,
     errors: Vec<ExportError>,
+    /// Left by a three-way merge, saved with the datapack on export
+    conflicts: Vec<ThreeWayConflict>,
+}
+
+impl DatapackGui {
+    /// Adds a cost when something is typed into the empty row at the end, which is shown even if it doesn't match
+    /// the search
+    fn add_missing_cost(&mut self, i: usize) {
+        if i == self.costs.len() {
+            self.costs.push(CostEntry::blank());
+            if let Some(results) = &mut self.search_results {
+                results.insert(i);
+            }
+        }
+    }
+
+    /// Indexes the costs to search, done once after they change rather than on every search
+    fn search_index(&mut self) -> &(RecipeIndex<'static>, BTreeMap<String, usize>) {
+        self.search_index.get_or_insert_with(|| {
+            let mut recipes = BTreeMap::new();
+            let mut positions = BTreeMap::new();
+            for (i, cost) in self.costs.iter().enumerate() {
+                if let Some(recipe) = cost.recipe() {
+                    //Where it would be exported to, so the location can be searched too
+                    let location = match self.config.naming.location(&recipe) {
+                        Some(location) if !recipes.contains_key(&location) => location,
+                        _ => format!("#{i}"),
+                    };
+                    positions.insert(location.clone(), i);
+                    recipes.insert(location, recipe);
+                }
+            }
+            (RecipeIndex::owned(recipes, Some(&self.item_checks.tags)), positions)
+        })
+    }
+
+    /// Finds the costs matching the search
+    ///
+    /// Edited costs stay shown until the search is changed or the costs are reloaded, so rows don't disappear
+    /// while being typed in
+    fn update_search(&mut self) {
+        let query: RecipeQuery = match self.search.parse() {
+            Ok(query) if !RecipeQuery::is_empty(&query) => query,
+            _ => {
+                self.search_results = None;
+                return;
+            }
+        };
+        let (index, positions) = self.search_index();
+        let results = index.query(&query).into_iter().map(|(location, _)| positions[location]).collect();
+        self.search_results = Some(results);
+    }
+
+    /// Whether a cost is shown with the current search
+    fn visible(&self, i: usize) -> bool {
+        self.search_results.as_ref().is_none_or(|results| results.contains(&i))
+    }
+
+    /// Works out amounts written as expressions, which can use the costs of other rows or the baselines
+    fn evaluate_expressions(&mut self) {
+        if self.costs.iter().all(|cost| cost.grist.iter().all(|g| g.expression.is_none())) {
+            return;
+        }
+        let formulas: Vec<(String, CostFormula)> = self
+            .costs
+            .iter()
+            .map(|cost| {
+                let item = cost.ingredient.as_ref().map(Ingredient::to_string).unwrap_or_default();
+                let terms = cost
+                    .grist
+                    .iter()
+                    .filter(|g| g.valid_name)
+                    .filter_map(|g| match (&g.expression, g.amount_string.trim().parse::<i32>()) {
+                        (Some(expression), _) => Some((Some(g.name.clone()), expression.clone())),
+                        (None, Ok(amount)) => Some((Some(g.name.clone()), GristExpression::Number(amount as f64))),
+                        (None, Err(_)) => None,
+                    })
+                    .collect();
+                (item, CostFormula { terms })
+            })
+            .collect();
+        let existing = |item: &str| self.resolver.item_cost(item).map(|(grist, _)| grist);
+        let results = evaluate_formulas(&formulas, existing);
+        //Each field is worked out on its own with the totals, so errors show next to the right field
+        let totals: BTreeMap<&str, _> = formulas
+            .iter()
+            .zip(&results)
+            .filter_map(|((item, _), result)| Some((item.as_str(), result.as_ref().ok()?)))
+            .collect();
+        let cost_of = |item: &str| totals.get(item).map(|totals| (*totals).clone()).or_else(|| existing(item));
+        for (cost, result) in self.costs.iter_mut().zip(&results) {
+            for g in &mut cost.grist {
+                let Some(expression) = &g.expression else {
+                    continue;
+                };
+                let amount = result
+                    .clone()
+                    .and_then(|_| expression.evaluate(Some(&g.name), &cost_of))
+                    .and_then(|grist| recipe_amounts(&grist))
+                    .map(|grist| grist.values().sum());
+                (g.amount, g.amount_error) = match amount {
+                    Ok(amount) => (Some(amount), None),
+                    Err(e) => (None, Some(e)),
+                };
+            }
+        }
+    }
+
+    /// Replaces the conflicting part with one side's version, or removes it if that side doesn't have it
+    fn resolve_conflict(&mut self, conflict: &ThreeWayConflict, value: Option<Value>) -> Result<(), String> {
+        let position = self.costs.iter().position(|cost| cost.location.as_ref() == Some(&conflict.id));
+        let parse_error = |e: serde_json::Error| format!("Couldn't read the version of {} picked, {e}", conflict.name);
+        match &conflict.part {
+            ConflictPart::Recipe => {
+                let recipe = value.map(serde_json::from_value::<Recipe>).transpose().map_err(parse_error)?;
+                self.combinations.remove(&conflict.id);
+                match recipe {
+                    Some(Recipe::GristCost(recipe)) => {
+                        let cost =
+                            CostEntry::from_recipe(conflict.id.clone(), recipe, &self.grist_registry, &self.item_checks);
+                        match position {
+                            Some(i) => self.costs[i] = cost,
+                            None => self.costs.push(cost),
+                        }
+                    }
+                    Some(recipe) => {
+                        if let Some(i) = position {
+                            self.costs.remove(i);
+                        }
+                        self.combinations.insert(conflict.id.clone(), recipe);
+                    }
+                    None => {
+                        if let Some(i) = position {
+                            self.costs.remove(i);
+                        }
+                    }
+                }
+            }
+            ConflictPart::Priority | ConflictPart::Grist(_) => {
+                let Some(i) = position else {
+                    return Err(format!("Couldn't find the {} to change", conflict.name));
+                };
+                let cost = &mut self.costs[i];
+                match (&conflict.part, value) {
+                    (ConflictPart::Grist(grist), value) => {
+                        let field = cost.grist.iter().position(|g| grist_resource(&g.name) == *grist);
+                        let amount = value.map(serde_json::from_value::<i32>).transpose().map_err(parse_error)?;
+                        match (field, amount) {
+                            (Some(j), Some(amount)) => cost.grist[j].set_amount(amount.to_string()),
+                            (Some(j), None) => {
+                                cost.grist.remove(j);
+                            }
+                            (None, Some(amount)) => {
+                                let name = grist.strip_prefix("minestuck:").unwrap_or(grist);
+                                cost.grist.push(GristField::new(name, amount, &self.grist_registry));
+                            }
+                            (None, None) => {}
+                        }
+                    }
+                    (_, Some(Value::Number(priority))) => cost.set_priority(priority.to_string()),
+                    (_, _) => cost.set_priority(String::new()),
+                }
+                self.check_priority(i);
+            }
+            ConflictPart::GristType => {
+                match value.map(serde_json::from_value::<GristType>).transpose().map_err(parse_error)? {
+                    Some(grist_type) => self.grist_types.insert(conflict.id.clone(), grist_type),
+                    None => self.grist_types.remove(&conflict.id),
+                };
+            }
+            ConflictPart::ItemTag => {
+                match value.map(serde_json::from_value::<Tag>).transpose().map_err(parse_error)? {
+                    Some(tag) => self.item_tags.insert(conflict.id.clone(), tag),
+                    None => self.item_tags.remove(&conflict.id),
+                };
+            }
+            ConflictPart::Mcmeta => {
+                self.mcmeta = value.map(serde_json::from_value).transpose().map_err(parse_error)?.unwrap_or_default();
+            }
+        }
+        self.evaluate_expressions();
+        self.search_index = None;
+        self.update_search();
+        Ok(())
+    }
+
+    /// Finds a baseline recipe with at least the same priority as a cost for any item it covers
+    fn check_priority(&mut self, i: usize) {
+        let cost = &mut self.costs[i];
+        cost.priority_warning = None;
+        let (Some(priority), Some(ingredient)) = (cost.priority, cost.ingredient.clone()) else {
+            return;
+        };
+        let Ok(items) = self.item_checks.tags.ingredient_items(&ingredient) else {
+            return;
+        };
+        let priority = priority.unwrap_or_else(|| default_priority(items.len()));
+        let recipe: Recipe = GristCostRecipe {
+            priority: None,
+            ingredient: ingredient.clone(),
+            grist_cost: BTreeMap::new(),
+            conditions: Default::default(),
+        }
+        .into();
+        //A baseline recipe at the same location is replaced rather than competing
+        let location = self.config.naming.location(&recipe);
+        let items: Vec<Ingredient> = match ingredient {
+            Ingredient::Nbt(_) => vec![ingredient],
+            _ => items.into_iter().map(Ingredient::Item).collect(),
+        };
+        for item in items {
+            let resolution = self.resolver.resolve(&item);
+            let winner = resolution.candidates.iter().find(|candidate| {
+                Some(candidate.location) != location.as_deref()
+                    && recipe_id(candidate.location).is_none_or(|id| !self.mcmeta.hides_recipe(&id))
+            });
+            if let Some(winner) = winner.filter(|winner| winner.priority >= priority) {
+                let relation = if winner.priority == priority { "ties with" } else { "loses to" };
+                let id = recipe_id(winner.location).unwrap_or_default();
+                cost.priority_warning = Some(format!("{relation} {id}, priority {}, for {item}", winner.priority));
+                return;
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -26,46 +272,210 @@ struct ExportError {
 #[derive(Default)]
 struct CostEntry {
     item_id: String,
+    /// Parsed when the item is typed, kept as loaded otherwise since the text may not parse back to the same
+    /// ingredient, such as NBT written for another loader
+    ingredient: Option<Ingredient>,
     valid_item: bool,
+    /// Set if a valid item isn't in the item registry or a tag can't be found
+    item_warning: Option<String>,
+    /// Which items the tags in the ingredient cover
+    tag_items: Option<String>,
+    priority_string: String,
+    /// None if invalid, Some(None) to leave it to Minestuck's default
+    priority: Option<Option<i32>>,
+    /// Set if a baseline recipe beats or ties with this one for any item
+    priority_warning: Option<String>,
     grist: Vec<GristField>,
+    /// Where it was loaded from, so merge conflicts can find it
+    location: Option<String>,
+    //Kept from the loaded recipe since they can't be edited here
+    conditions: Conditions,
+}
+
+/// What item fields are checked against
+struct ItemChecks {
+    loader: Loader,
+    registry: ItemRegistry,
+    tags: ItemTags,
+    /// Without tag sources even vanilla tags are unknown, so only tags that are found are shown
+    check_tags: bool,
 }
 
 impl CostEntry {
-    fn new(item_id: &str, grist: Vec<GristField>) -> CostEntry {
-        CostEntry {
-            item_id: item_id.to_string(),
-            valid_item: validate_resource_location(item_id),
+    fn new(ingredient: Ingredient, priority: Option<i32>, grist: Vec<GristField>, checks: &ItemChecks) -> CostEntry {
+        let mut entry = CostEntry {
             grist,
+            ..Default::default()
+        };
+        entry.set_ingredient(ingredient.to_string(), Some(ingredient), checks);
+        entry.set_priority(priority.map(|priority| priority.to_string()).unwrap_or_default());
+        entry
+    }
+
+    fn from_recipe(location: String, recipe: GristCostRecipe, registry: &GristRegistry, checks: &ItemChecks) -> CostEntry {
+        let grist = recipe
+            .grist_cost
+            .iter()
+            .map(|(grist, amount)| GristField::new(grist.strip_prefix("minestuck:").unwrap_or(grist), *amount, registry))
+            .collect();
+        CostEntry {
+            location: Some(location),
+            conditions: recipe.conditions,
+            ..CostEntry::new(recipe.ingredient, recipe.priority, grist, checks)
+        }
+    }
+
+    /// A new row, with the priority the command line tools use
+    fn blank() -> CostEntry {
+        let mut entry = CostEntry::default();
+        entry.set_priority(GENERATED_PRIORITY.to_string());
+        entry
+    }
+
+    fn set_priority(&mut self, priority: String) {
+        self.priority = if priority.trim().is_empty() {
+            Some(None)
+        } else {
+            priority.trim().parse().ok().map(Some)
+        };
+        self.priority_string = priority;
+    }
+
+    /// The recipe this makes, if everything in it is valid
+    fn recipe(&self) -> Option<Recipe> {
+        if !self.valid_item || !self.grist.iter().all(|g| g.amount.is_some() && g.valid_name) {
+            return None;
+        }
+        Some(
+            GristCostRecipe {
+                priority: self.priority?,
+                ingredient: self.ingredient.clone()?,
+                grist_cost: self.grist.iter().map(|g| (grist_resource(&g.name), g.amount.unwrap())).collect(),
+                conditions: self.conditions.clone(),
+            }
+            .into(),
+        )
+    }
+
+    fn set_item(&mut self, item_id: String, checks: &ItemChecks) {
+        let ingredient = Ingredient::parse(&item_id, checks.loader).ok();
+        self.set_ingredient(item_id, ingredient, checks);
+    }
+
+    fn set_ingredient(&mut self, item_id: String, ingredient: Option<Ingredient>, checks: &ItemChecks) {
+        self.item_id = item_id;
+        self.valid_item = ingredient.as_ref().is_some_and(Ingredient::is_valid);
+        self.ingredient = ingredient;
+        self.item_warning = None;
+        self.tag_items = None;
+        let Some(ingredient) = self.ingredient.as_ref().filter(|_| self.valid_item) else {
+            return;
+        };
+        let registry = &checks.registry;
+        if let Some(unknown) = ingredient.item_ids().into_iter().find(|item| !registry.is_empty() && !registry.contains(item)) {
+            self.item_warning = Some(match registry.suggest(unknown) {
+                Some(suggestion) => format!("did you mean {suggestion}?"),
+                None => format!("unknown item {unknown}"),
+            });
+        }
+        if !ingredient.tag_ids().is_empty() {
+            match checks.tags.ingredient_items(ingredient) {
+                Ok(items) => {
+                    let shown: Vec<_> = items.iter().take(5).map(String::as_str).collect();
+                    let more = if items.len() > shown.len() { ", ..." } else { "" };
+                    self.tag_items = Some(format!("covers {} items: {}{more}", items.len(), shown.join(", ")));
+                }
+                Err(e) if checks.check_tags => self.item_warning = Some(e.to_string()),
+                Err(_) => {}
+            }
         }
     }
 }
 
+/// Lowercases ids as they're typed, but not NBT where case matters
+fn lowercase_ids(s: &str) -> String {
+    let mut depth = 0;
+    s.chars()
+        .map(|c| {
+            match c {
+                '{' | '[' => depth += 1,
+                '}' | ']' => depth -= 1,
+                _ => {}
+            }
+            if depth > 0 {
+                c
+            } else {
+                c.to_ascii_lowercase()
+            }
+        })
+        .collect()
+}
+
 #[derive(Default)]
 struct GristField {
     name: String,
     valid_name: bool,
+    /// Valid names can still be typos of a known grist type
+    known_name: bool,
+    suggestion: Option<String>,
     amount_string: String,
+    /// Worked out from the expression if there is one
     amount: Option<i32>,
+    /// Set if the amount isn't a plain number
+    expression: Option<GristExpression>,
+    amount_error: Option<String>,
 }
 
 impl GristField {
-    fn new(name: &str, amount: i32) -> GristField {
-        GristField {
-            name: name.to_string(),
-            valid_name: validate_resource_location(&grist_resource(name)),
+    fn new(name: &str, amount: i32, registry: &GristRegistry) -> GristField {
+        let mut field = GristField {
             amount_string: amount.to_string(),
             amount: Some(amount),
+            ..Default::default()
+        };
+        field.set_name(name.to_string(), registry);
+        field
+    }
+
+    fn set_amount(&mut self, amount: String) {
+        self.expression = None;
+        self.amount_error = None;
+        self.amount = amount.trim().parse().ok();
+        if self.amount.is_none() && !amount.trim().is_empty() {
+            match amount.parse() {
+                Ok(expression) => self.expression = Some(expression),
+                Err(e) => self.amount_error = Some(e),
+            }
         }
+        self.amount_string = amount;
+    }
+
+    fn set_name(&mut self, name: String, registry: &GristRegistry) {
+        let id = grist_resource(&name);
+        self.name = name;
+        self.valid_name = validate_resource_location(&id);
+        self.known_name = registry.contains(&id);
+        self.suggestion = if self.known_name {
+            None
+        } else {
+            registry
+                .suggest(&id)
+                .map(|suggestion| suggestion.strip_prefix("minestuck:").unwrap_or(suggestion).to_string())
+        };
     }
 }
 
 #[derive(Debug, Clone)]
 enum Message {
     ItemId(usize, String),
+    Priority(usize, String),
     GristName(usize, usize, String),
     GristAmount(usize, usize, String),
     Export,
     Goto(usize),
+    ToggleBaseline(usize),
+    Search(String),
+    ResolveConflict(usize, MergeSide),
 }
 
 impl Application for DatapackGui {
@@ -78,39 +488,67 @@ impl Application for DatapackGui {
     type Flags = ();
 
     fn new(_flags: Self::Flags) -> (Self, Command<Message>) {
-        //TODO load existing grist costs
+        let config = Config::load(CONFIG_FILE);
         let datapack = Datapack::load("./datapack/");
+        let baselines = config.load_baselines();
+        let mut grist_registry = GristRegistry::new(&config);
+        grist_registry.add_datapack(&datapack);
+        for baseline in &baselines {
+            grist_registry.add_datapack(baseline);
+        }
+        let item_checks = ItemChecks {
+            loader: config.loader,
+            registry: config.load_item_registry(),
+            tags: config.load_item_tags(&baselines, &datapack),
+            check_tags: !config.tag_sources.is_empty(),
+        };
+        let mcmeta = datapack.mcmeta;
+        let grist_types = datapack.grist_types;
+        let item_tags = datapack.item_tags;
+        let mut combinations = BTreeMap::new();
+        let resolver = config.cost_resolver(&baselines, &Datapack::new(), "");
+        let baseline = baselines
+            .into_iter()
+            .flat_map(|baseline| baseline.recipes.into_keys())
+            .filter_map(|location| recipe_id(&location))
+            .collect();
         let costs = datapack
             .recipes
             .into_iter()
-            .filter_map(|(_, recipe)| match recipe {
-                Recipe::GristCost(recipe) => match &recipe.ingredient {
-                    Ingredient::Item(id) => Some(CostEntry::new(
-                        id,
-                        recipe
-                            .grist_cost
-                            .iter()
-                            .map(|(grist, amount)| GristField::new(grist.strip_prefix("minestuck:").unwrap_or(grist), *amount))
-                            .collect(),
-                    )),
-                    _ => None,
-                },
-                Recipe::Combination(recipe) => match (&recipe.input1, &recipe.input2, &recipe.output) {
-                    (Ingredient::Item(_), Ingredient::Item(_), ResultItem::Item(_)) => Some(CostEntry::new(
-                        "",
-                        vec![GristField::new("", 0)]
-                    )),
-                    (_, _, _) => None,
-                },
+            .filter_map(|(location, recipe)| match recipe {
+                Recipe::GristCost(recipe) => Some(CostEntry::from_recipe(location, recipe, &grist_registry, &item_checks)),
+                //Kept as is since they can't be edited here yet
+                Recipe::Combination(_) => {
+                    combinations.insert(location, recipe);
+                    None
+                }
             })
             .collect();
-        (
-            DatapackGui {
-                costs,
-                errors: Vec::new(),
-            },
-            Command::none(),
-        )
+        let conflicts = load_conflicts("./datapack/").unwrap_or_else(|e| {
+            eprintln!("{e}");
+            vec![]
+        });
+        let mut gui = DatapackGui {
+            config,
+            grist_registry,
+            item_checks,
+            mcmeta,
+            grist_types,
+            item_tags,
+            combinations,
+            costs,
+            baseline,
+            resolver,
+            search: String::new(),
+            search_index: None,
+            search_results: None,
+            errors: Vec::new(),
+            conflicts,
+        };
+        for i in 0..gui.costs.len() {
+            gui.check_priority(i);
+        }
+        (gui, Command::none())
     }
 
     fn title(&self) -> String {
@@ -120,36 +558,38 @@ impl Application for DatapackGui {
     fn update(&mut self, message: Message) -> Command<Message> {
         match message {
             Message::ItemId(i, new_id) => {
-                if i == self.costs.len() {
-                    self.costs.push(Default::default());
-                }
-                let cost = &mut self.costs[i];
-                cost.item_id = new_id.to_lowercase();
-                cost.valid_item = validate_resource_location(&cost.item_id);
+                self.add_missing_cost(i);
+                self.search_index = None;
+                self.costs[i].set_item(lowercase_ids(&new_id), &self.item_checks);
+                self.check_priority(i);
+                self.evaluate_expressions();
+                Command::none()
+            }
+            Message::Priority(i, new_priority) => {
+                self.add_missing_cost(i);
+                self.search_index = None;
+                self.costs[i].set_priority(new_priority);
+                self.check_priority(i);
                 Command::none()
             }
             Message::GristName(i, j, new_name) => {
-                if i == self.costs.len() {
-                    self.costs.push(Default::default());
-                }
+                self.add_missing_cost(i);
+                self.search_index = None;
                 if j == self.costs[i].grist.len() {
                     self.costs[i].grist.push(Default::default());
                 }
-                let grist = &mut self.costs[i].grist[j];
-                grist.name = new_name.to_lowercase();
-                grist.valid_name = validate_resource_location(&grist_resource(&grist.name));
+                self.costs[i].grist[j].set_name(new_name.to_lowercase(), &self.grist_registry);
+                self.evaluate_expressions();
                 Command::none()
             }
             Message::GristAmount(i, j, new_amount) => {
-                if i == self.costs.len() {
-                    self.costs.push(Default::default());
-                }
+                self.add_missing_cost(i);
+                self.search_index = None;
                 if j == self.costs[i].grist.len() {
                     self.costs[i].grist.push(Default::default());
                 }
-                let grist = &mut self.costs[i].grist[j];
-                grist.amount = new_amount.parse().ok();
-                grist.amount_string = new_amount;
+                self.costs[i].grist[j].set_amount(new_amount);
+                self.evaluate_expressions();
                 Command::none()
             }
             Message::Export => {
@@ -162,40 +602,46 @@ impl Application for DatapackGui {
                             .all(|g| g.amount_string.is_empty() && g.name.is_empty()))
                 });
 
-                let mut datapack = Datapack::new();
+                let mut datapack = Datapack {
+                    mcmeta: self.mcmeta.clone(),
+                    recipes: self.combinations.clone(),
+                    grist_types: self.grist_types.clone(),
+                    item_tags: self.item_tags.clone(),
+                };
+                let mut exported = vec![];
                 for (i, cost) in self.costs.iter().enumerate() {
+                    let Some(priority) = cost.priority else {
+                        self.errors.push(ExportError {
+                            text: format!("Invalid priority for {}", cost.item_id), position: i, invalid: true});
+                        continue;
+                    };
                     if cost.valid_item {
                         if cost
                             .grist
                             .iter()
                             .all(|g| g.amount.is_some() && g.valid_name)
                         {
-                            let (domain, path) = cost.item_id.split_once(':').unwrap();
-                            let ingredient = Ingredient::Item(cost.item_id.to_string());
+                            let ingredient = cost.ingredient.clone().unwrap();
                             let grist_cost = cost
                                 .grist
                                 .iter()
                                 .map(|g| (grist_resource(&g.name), g.amount.unwrap()))
                                 .collect();
                             //Not invalid but probably want to mention anyways
-                            let recipe = GristCostRecipe {
-                                priority: Some(101),
+                            let recipe: Recipe = GristCostRecipe {
+                                priority,
                                 ingredient,
                                 grist_cost,
-                            };
-                            if datapack
-                                .recipes
-                                .insert(
-                                    format!("data/minestuck/recipes/grist_costs/{domain}/{path}"),
-                                    recipe.into(),
-                                )
-                                .is_some()
-                            {
-                                self.errors.push(ExportError {
-                                    text: format!("Duplicate item {}", cost.item_id),
+                                conditions: cost.conditions.clone(),
+                            }
+                            .into();
+                            match datapack.add(&self.config, recipe) {
+                                Ok(location) => exported.push((i, location)),
+                                Err(e) => self.errors.push(ExportError {
+                                    text: e,
                                     position: i,
                                     invalid: true
-                                });
+                                }),
                             }
                             //Not invalid but should probably mention it anyways
                             if cost.grist.is_empty() {
@@ -211,45 +657,123 @@ impl Application for DatapackGui {
                                 text: format!("Invalid item {}", cost.item_id), position: i, invalid: true});
                     }
                 }
+                //Rows added here or renamed by the naming scheme have to be found by where they are now
+                for (i, location) in exported {
+                    if let Some(old) = self.costs[i].location.replace(location.clone()) {
+                        for conflict in &mut self.conflicts {
+                            let is_recipe = !matches!(conflict.part, ConflictPart::ItemTag | ConflictPart::GristType);
+                            if is_recipe && conflict.id == old {
+                                conflict.id = location.clone();
+                            }
+                        }
+                    }
+                }
                 datapack.save("./datapack/");
+                save_conflicts("./datapack/", &self.conflicts);
+                //Costs were removed, so positions changed
+                self.search_index = None;
+                self.update_search();
                 Command::none()
             }
             Message::Goto(i) => {
-                let heights: Vec<f32> = self.costs.iter().map(|cost| (cost.grist.len() as f32 + 1.0) * 30.0 + 4.0).collect();
-                let total_height: f32 = heights.iter().sum();
-                let cost_position: f32 = heights.iter().take(i).sum();
+                //A cost hidden by the search can't be scrolled to
+                if !self.visible(i) {
+                    self.search.clear();
+                    self.search_results = None;
+                }
+                let heights: Vec<(usize, f32)> = self
+                    .costs
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| self.visible(*j))
+                    .map(|(j, cost)| (j, (cost.grist.len() as f32 + 1.0) * 30.0 + 4.0))
+                    .collect();
+                let total_height: f32 = heights.iter().map(|(_, height)| height).sum();
+                let cost_position: f32 = heights.iter().take_while(|(j, _)| *j < i).map(|(_, height)| height).sum();
                 let y = cost_position / total_height;
                 scrollable::snap_to(
                     scrollable::Id::new("costs"),
                     scrollable::RelativeOffset { x: 0.0, y },
                 )
             },
+            Message::Search(search) => {
+                self.search = search;
+                self.update_search();
+                Command::none()
+            }
+            Message::ResolveConflict(i, side) => {
+                let conflict = self.conflicts[i].clone();
+                match self.resolve_conflict(&conflict, conflict.value(side).cloned()) {
+                    Ok(()) => {
+                        self.conflicts.remove(i);
+                    }
+                    Err(e) => eprintln!("{e}"),
+                }
+                Command::none()
+            }
+            Message::ToggleBaseline(i) => {
+                let id = &self.baseline[i];
+                if !self.mcmeta.unhide_recipe(id) {
+                    self.mcmeta.hide_recipe(id);
+                }
+                for i in 0..self.costs.len() {
+                    self.check_priority(i);
+                }
+                Command::none()
+            }
         }
     }
 
-    fn view(&self) -> Element<Message> {
+    fn view(&self) -> Element<'_, Message> {
         let mut cost_column = column(
             self.costs
                 .iter()
                 .enumerate()
+                .filter(|(i, _)| self.visible(*i))
                 .map(|(i, cost)| {
-                    let item_style = if cost.valid_item {
-                        TextInputTheme::Valid
+                    let item_style = if !cost.valid_item {
+                        TextInputTheme::Invalid
+                    } else if cost.item_warning.is_some() {
+                        TextInputTheme::Warning
                     } else {
+                        TextInputTheme::Valid
+                    };
+                    let item_input = text_input("modid:itemname", &cost.item_id, move |s| Message::ItemId(i, s))
+                        .style(theme::TextInput::Custom(Box::new(item_style)))
+                        .width(200);
+                    let mut item_column = column![item_input];
+                    if let Some(warning) = &cost.item_warning {
+                        item_column = item_column.push(text(warning).style(WARNING_COLOR));
+                    }
+                    if let Some(tag_items) = &cost.tag_items {
+                        item_column = item_column.push(text(tag_items).size(14).width(200));
+                    }
+                    let priority_style = if cost.priority.is_none() {
                         TextInputTheme::Invalid
+                    } else if cost.priority_warning.is_some() {
+                        TextInputTheme::Warning
+                    } else {
+                        TextInputTheme::Valid
                     };
-                    let cost_row = row![text_input("modid:itemname", &cost.item_id, move |s| {
-                        Message::ItemId(i, s)
-                    })
-                    .style(theme::TextInput::Custom(Box::new(item_style)))
-                    .width(200)];
+                    item_column = item_column.push(row![
+                        text("priority"),
+                        text_input("default", &cost.priority_string, move |s| Message::Priority(i, s))
+                            .style(theme::TextInput::Custom(Box::new(priority_style)))
+                            .width(80),
+                    ]);
+                    if let Some(warning) = &cost.priority_warning {
+                        item_column = item_column.push(text(warning).size(14).width(200).style(WARNING_COLOR));
+                    }
+                    let cost_row = row![item_column];
 
                     let mut grist_column = column!();
                     for (j, grist) in cost.grist.iter().enumerate() {
-                        let name_style = if grist.valid_name {
-                            TextInputTheme::Valid
-                        } else {
+                        let name_style = if !grist.valid_name {
                             TextInputTheme::Invalid
+                        } else if !grist.known_name {
+                            TextInputTheme::Warning
+                        } else {
+                            TextInputTheme::Valid
                         };
                         let amount_style = if grist.amount.is_some() {
                             TextInputTheme::Valid
@@ -264,9 +788,26 @@ impl Application for DatapackGui {
                             text_input("amount", &grist.amount_string, move |s| {
                                 Message::GristAmount(i, j, s)
                             })
-                            .width(100)
+                            .width(150)
                             .style(theme::TextInput::Custom(Box::new(amount_style))),
                         ].height(30);
+                        let grist_row = match (&grist.amount_error, grist.expression.is_some(), grist.amount) {
+                            (Some(error), _, _) => grist_row.push(text(error).size(14).style(WARNING_COLOR)),
+                            //Only the amount is exported, so the expression is gone when the pack is reopened
+                            (None, true, Some(amount)) => {
+                                grist_row.push(text(format!("= {amount}, exported as just the amount")))
+                            }
+                            _ => grist_row,
+                        };
+                        let grist_row = if grist.valid_name && !grist.known_name {
+                            let warning = match &grist.suggestion {
+                                Some(suggestion) => format!("did you mean {suggestion}?"),
+                                None => "unknown grist".to_string(),
+                            };
+                            grist_row.push(text(warning).style(WARNING_COLOR))
+                        } else {
+                            grist_row
+                        };
                         grist_column = grist_column.push(grist_row);
                     }
                     let grist_count = cost.grist.len();
@@ -277,7 +818,7 @@ impl Application for DatapackGui {
                         text_input("amount", "", move |s| {
                             Message::GristAmount(i, grist_count, s)
                         })
-                        .width(100),
+                        .width(150),
                     ];
                     grist_column = grist_column.push(empty_grist_row);
 
@@ -326,9 +867,49 @@ impl Application for DatapackGui {
                 .collect(),
         ));
 
-        let export_column = column![button(text("Export")).on_press(Message::Export), errors,];
+        let search_style = if self.search.parse::<RecipeQuery>().is_ok() {
+            TextInputTheme::Valid
+        } else {
+            TextInputTheme::Invalid
+        };
+        let search = text_input("search, e.g. build>10 namespace=modid", &self.search, Message::Search)
+            .style(theme::TextInput::Custom(Box::new(search_style)))
+            .width(300);
+        let conflicts = scrollable(column(
+            self.conflicts
+                .iter()
+                .enumerate()
+                .map(|(i, conflict)| {
+                    column![
+                        text(conflict).size(14).style(WARNING_COLOR),
+                        row![
+                            button(text("Base")).on_press(Message::ResolveConflict(i, MergeSide::Base)),
+                            button(text("Ours")).on_press(Message::ResolveConflict(i, MergeSide::Ours)),
+                            button(text("Theirs")).on_press(Message::ResolveConflict(i, MergeSide::Theirs)),
+                        ]
+                    ]
+                    .into()
+                })
+                .collect(),
+        ));
+        let export_column = column![search, button(text("Export")).on_press(Message::Export), errors, conflicts];
 
-        let content = row![scrollable(cost_column).id(scrollable::Id::new("costs")), export_column];
+        let baseline = scrollable(column(
+            self.baseline
+                .iter()
+                .enumerate()
+                .map(|(i, id)| {
+                    let (label, toggle) = if self.mcmeta.hides_recipe(id) {
+                        (text(id).style(Color::from_rgb(0.5, 0.5, 0.5)), "Restore")
+                    } else {
+                        (text(id), "Remove")
+                    };
+                    row![label, button(text(toggle)).on_press(Message::ToggleBaseline(i))].into()
+                })
+                .collect(),
+        ));
+
+        let content = row![scrollable(cost_column).id(scrollable::Id::new("costs")), export_column, baseline];
 
         content.into()
     }
@@ -356,11 +937,14 @@ impl container::StyleSheet for ContainerTheme {
     }
 }
 
+const WARNING_COLOR: Color = Color::from_rgb(0.9, 0.5, 0.1);
+
 #[derive(Default, Copy, Clone, PartialEq, Eq)]
 enum TextInputTheme {
     #[default]
     Valid,
     Invalid,
+    Warning,
 }
 
 impl text_input::StyleSheet for TextInputTheme {
@@ -379,10 +963,10 @@ impl text_input::StyleSheet for TextInputTheme {
     }
 
     fn value_color(&self, style: &Self::Style) -> Color {
-        if *self == TextInputTheme::Valid {
-            style.value_color(&Default::default())
-        } else {
-            Color::from([0.9, 0.1, 0.1])
+        match self {
+            TextInputTheme::Valid => style.value_color(&Default::default()),
+            TextInputTheme::Invalid => Color::from([0.9, 0.1, 0.1]),
+            TextInputTheme::Warning => WARNING_COLOR,
         }
     }
 
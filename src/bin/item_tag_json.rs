@@ -0,0 +1,61 @@
+use std::fs::File;
+use std::io::{stdin, BufRead, BufReader};
+
+use minestuck_datapack_generator::{
+    split_columns, validate_resource_location, Config, Datapack, Tag, TagEntry, CONFIG_FILE,
+};
+
+fn main() {
+    let reader: Box<dyn BufRead> = if let Some(arg) = std::env::args().nth(1) {
+        let file = File::open(arg).unwrap();
+        Box::new(BufReader::new(file))
+    } else {
+        let stdin = stdin();
+        Box::new(stdin.lock())
+    };
+    let config = Config::load(CONFIG_FILE);
+    let mut datapack = Datapack::new();
+    for line in reader.lines() {
+        let input = line.unwrap();
+
+        let mut columns = split_columns(&input).into_iter();
+        let id = columns.next().unwrap();
+        let id = id.strip_prefix('#').unwrap_or(id);
+        if id.is_empty() {
+            continue;
+        }
+        if !validate_resource_location(id) {
+            eprintln!("Invalid tag id {id}");
+            continue;
+        }
+        let mut tag = Tag::default();
+        for column in columns.filter(|column| !column.is_empty()) {
+            if column == "replace" {
+                tag.replace = true;
+            } else {
+                tag.values.push(TagEntry::parse(column));
+            }
+        }
+        if !tag.is_valid() {
+            eprintln!("Invalid tag: \"{tag:#?}\"");
+        } else if datapack.item_tags.insert(id.to_string(), tag).is_some() {
+            eprintln!("Duplicate tag {id}");
+        }
+    }
+
+    let tags = config.load_item_tags(&config.load_baselines(), &datapack);
+    //Without tag sources even vanilla tags are unknown
+    if !config.tag_sources.is_empty() {
+        for id in datapack.item_tags.keys() {
+            if let Err(e) = tags.resolve(id) {
+                eprintln!("{e} in #{id}");
+            }
+        }
+    }
+    for (tag, item) in tags.unknown_items(&config.load_item_registry()) {
+        if datapack.item_tags.contains_key(tag) {
+            eprintln!("Unknown item {item} in #{tag}");
+        }
+    }
+    datapack.write_item_tags(".");
+}
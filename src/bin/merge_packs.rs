@@ -0,0 +1,57 @@
+use minestuck_datapack_generator::{Datapack, DescriptionMerge, MergePolicy};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut paths = vec![];
+    let mut policy = MergePolicy::Error;
+    let mut description = DescriptionMerge::Left;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--policy" => match args.next().unwrap_or_default().parse() {
+                Ok(parsed) => policy = parsed,
+                Err(e) => {
+                    eprintln!("{e}");
+                    return;
+                }
+            },
+            "--description" => description = args.next().unwrap_or_default().parse().unwrap(),
+            _ => paths.push(arg),
+        }
+    }
+    let [output, first, rest @ ..] = &paths[..] else {
+        eprintln!("Give the output directory and the datapacks to merge, each a directory or zip");
+        return;
+    };
+    if rest.is_empty() {
+        eprintln!("Give at least two datapacks to merge");
+        return;
+    }
+
+    let mut merged = Datapack::load(first);
+    let mut failed = false;
+    for path in rest {
+        match merged.merge(&Datapack::load(path), policy, &description) {
+            Ok((pack, conflicts)) => {
+                if !conflicts.is_empty() {
+                    println!("While adding {path}:");
+                    for conflict in conflicts {
+                        println!("  {conflict}");
+                    }
+                }
+                merged = pack;
+            }
+            Err(conflicts) => {
+                eprintln!("{path} conflicts with the packs before it:");
+                for conflict in conflicts {
+                    eprintln!("  {conflict}");
+                }
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        eprintln!("Nothing was written, pick a --policy to resolve the conflicts");
+        return;
+    }
+    merged.save(output);
+}
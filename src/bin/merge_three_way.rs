@@ -0,0 +1,26 @@
+use std::path::Path;
+
+use minestuck_datapack_generator::{save_conflicts, Datapack, MERGE_CONFLICT_FILE};
+
+fn main() {
+    let paths: Vec<String> = std::env::args().skip(1).collect();
+    let (base, ours, theirs, output) = match &paths[..] {
+        [base, ours, theirs] => (base, ours, theirs, "./datapack/"),
+        [base, ours, theirs, output] => (base, ours, theirs, output.as_str()),
+        _ => {
+            eprintln!("Give the base, our and their datapack, each a directory or zip, and optionally where to write the merged one");
+            return;
+        }
+    };
+    let (merged, conflicts) =
+        Datapack::three_way_merge(&Datapack::load(base), &Datapack::load(ours), &Datapack::load(theirs));
+    merged.save(output);
+    for conflict in &conflicts {
+        println!("{conflict}");
+    }
+    if !conflicts.is_empty() {
+        let path = Path::new(output).join(MERGE_CONFLICT_FILE);
+        println!("Conflicts written to {}, resolve them in the GUI", path.display());
+    }
+    save_conflicts(output, &conflicts);
+}
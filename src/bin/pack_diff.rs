@@ -0,0 +1,26 @@
+use minestuck_datapack_generator::{Datapack, DiffFormat};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut paths = vec![];
+    let mut format = DiffFormat::Text;
+    while let Some(arg) = args.next() {
+        if arg == "--format" {
+            match args.next().unwrap_or_default().parse() {
+                Ok(parsed) => format = parsed,
+                Err(e) => {
+                    eprintln!("{e}");
+                    return;
+                }
+            }
+        } else {
+            paths.push(arg);
+        }
+    }
+    let [old, new] = &paths[..] else {
+        eprintln!("Give the old and new datapack, each a directory or zip");
+        return;
+    };
+    let diff = Datapack::load(old).diff(&Datapack::load(new));
+    print!("{}", diff.write(format));
+}
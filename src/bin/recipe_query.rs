@@ -0,0 +1,35 @@
+use std::path::Path;
+
+use minestuck_datapack_generator::{Config, Datapack, RecipeQuery, CONFIG_FILE};
+
+fn main() {
+    let config = Config::load(CONFIG_FILE);
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    //Baselines are only searched when asked, since they're usually much bigger
+    let search_baselines = args.iter().any(|arg| arg == "--baselines");
+    args.retain(|arg| arg != "--baselines");
+    let query: RecipeQuery = match args.join(" ").parse() {
+        Ok(query) => query,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+
+    let datapack = if Path::new("data").is_dir() {
+        Datapack::load(".")
+    } else {
+        Datapack::new()
+    };
+    let baselines = if search_baselines {
+        config.load_baselines()
+    } else {
+        vec![]
+    };
+    let tags = config.load_item_tags(&baselines, &datapack);
+    for pack in baselines.iter().chain([&datapack]) {
+        for (location, recipe) in pack.index_with_tags(&tags).query(&query) {
+            println!("{location}: {recipe}");
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use std::path::Path;
+
+use minestuck_datapack_generator::{parse_constraints, solve_costs, Config, Datapack, Recipe, CONFIG_FILE};
+
+fn main() {
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("Give a file of constraints, such as \"minecraft:iron_block = 9 * minecraft:iron_ingot\"");
+        return;
+    };
+    let constraints = match parse_constraints(&std::fs::read_to_string(&path).unwrap()) {
+        Ok(constraints) => constraints,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    let config = Config::load(CONFIG_FILE);
+    let datapack = if Path::new("data").is_dir() {
+        Datapack::load(".")
+    } else {
+        Datapack::new()
+    };
+    //Items that aren't constrained keep the cost they already have
+    let resolver = config.cost_resolver(&config.load_baselines(), &datapack, "this datapack");
+    let solution = solve_costs(&constraints, |item| resolver.item_cost(item).map(|(grist, _)| grist));
+    for problem in &solution.problems {
+        eprintln!("Can't meet {problem}");
+    }
+    for item in &solution.unsolved {
+        eprintln!("No cost could be worked out for {item}");
+    }
+    //Costs are printed in the form grist_json reads
+    for recipe in solution.recipes() {
+        println!("{}", Recipe::GristCost(recipe));
+    }
+}
@@ -0,0 +1,34 @@
+use std::collections::BTreeMap;
+
+use minestuck_datapack_generator::{load_pack_mapping, Config, Datapack, CONFIG_FILE};
+
+fn main() {
+    let config = Config::load(CONFIG_FILE);
+    let mut args = std::env::args().skip(1);
+    let mut output = None;
+    let mut mapping = BTreeMap::new();
+    while let Some(arg) = args.next() {
+        if arg == "--mapping" {
+            match load_pack_mapping(args.next().unwrap_or_default()) {
+                Ok(loaded) => mapping = loaded,
+                Err(e) => {
+                    eprintln!("{e}");
+                    return;
+                }
+            }
+        } else {
+            output = Some(arg);
+        }
+    }
+    let Some(output) = output else {
+        eprintln!("Give the directory to write a pack for each mod to");
+        return;
+    };
+
+    let packs = Datapack::load(".").split(&mapping, &config.split, config.loader);
+    for (name, pack) in packs {
+        let recipes = pack.recipes.len();
+        pack.save(std::path::Path::new(&output).join(&name));
+        println!("{name}: recipes {recipes}, grist types {}, item tags {}", pack.grist_types.len(), pack.item_tags.len());
+    }
+}
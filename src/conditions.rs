@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+
+use serde::{de::Error, ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Value};
+
+use crate::Loader;
+
+/// Namespaces that are always loaded alongside a Minestuck datapack, so never need a mod_loaded condition
+pub const ALWAYS_LOADED: [&str; 2] = ["minecraft", "minestuck"];
+
+/// Conditions that all have to be met for a recipe to be loaded
+///
+/// Written as `conditions` for Forge or `neoforge:conditions` for NeoForge, and left out if empty
+#[derive(Debug, Clone, Default, Eq)]
+pub struct Conditions {
+    pub loader: Loader,
+    pub conditions: Vec<Condition>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    ModLoaded(String),
+    ItemExists(String),
+    TagEmpty(String),
+    Not(Box<Condition>),
+    And(Vec<Condition>),
+    Or(Vec<Condition>),
+}
+
+impl Conditions {
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+    }
+
+    /// Requires a mod to be loaded, unless that is already a condition
+    pub fn require_mod(&mut self, mod_id: &str) {
+        let condition = Condition::ModLoaded(mod_id.to_string());
+        if !self.conditions.contains(&condition) {
+            self.conditions.push(condition);
+        }
+    }
+}
+
+/// No conditions are the same whichever loader they would have been written for
+impl PartialEq for Conditions {
+    fn eq(&self, other: &Self) -> bool {
+        self.conditions == other.conditions && (self.is_empty() || self.loader == other.loader)
+    }
+}
+
+impl Condition {
+    fn to_json(&self, loader: Loader) -> Value {
+        match self {
+            Condition::ModLoaded(mod_id) => json!({ "type": format!("{loader}:mod_loaded"), "modid": mod_id }),
+            Condition::ItemExists(item) => json!({ "type": format!("{loader}:item_exists"), "item": item }),
+            Condition::TagEmpty(tag) => json!({ "type": format!("{loader}:tag_empty"), "tag": tag }),
+            Condition::Not(condition) => {
+                json!({ "type": format!("{loader}:not"), "value": condition.to_json(loader) })
+            }
+            Condition::And(conditions) => json!({
+                "type": format!("{loader}:and"),
+                "values": conditions.iter().map(|c| c.to_json(loader)).collect::<Vec<_>>(),
+            }),
+            Condition::Or(conditions) => json!({
+                "type": format!("{loader}:or"),
+                "values": conditions.iter().map(|c| c.to_json(loader)).collect::<Vec<_>>(),
+            }),
+        }
+    }
+
+    fn from_json(value: &Value) -> Result<Condition, String> {
+        let condition_type = value
+            .get("type")
+            .and_then(Value::as_str)
+            .ok_or_else(|| format!("Condition has no type: {value}"))?;
+        let string = |key: &str| {
+            value
+                .get(key)
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| format!("{condition_type} condition needs {key}"))
+        };
+        let values = || {
+            value
+                .get("values")
+                .and_then(Value::as_array)
+                .ok_or_else(|| format!("{condition_type} condition needs values"))?
+                .iter()
+                .map(Condition::from_json)
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let name = condition_type
+            .strip_prefix("forge:")
+            .or_else(|| condition_type.strip_prefix("neoforge:"))
+            .unwrap_or(condition_type);
+        match name {
+            "mod_loaded" => string("modid").map(Condition::ModLoaded),
+            "item_exists" => string("item").map(Condition::ItemExists),
+            "tag_empty" => string("tag").map(Condition::TagEmpty),
+            "not" => {
+                let inner = value
+                    .get("value")
+                    .ok_or_else(|| format!("{condition_type} condition needs value"))?;
+                Condition::from_json(inner).map(|c| Condition::Not(Box::new(c)))
+            }
+            "and" => values().map(Condition::And),
+            "or" => values().map(Condition::Or),
+            _ => Err(format!("Unsupported condition type {condition_type}")),
+        }
+    }
+}
+
+impl Serialize for Conditions {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        if !self.is_empty() {
+            let key = match self.loader {
+                Loader::Forge => "conditions",
+                Loader::NeoForge => "neoforge:conditions",
+            };
+            let conditions: Vec<_> = self.conditions.iter().map(|c| c.to_json(self.loader)).collect();
+            map.serialize_entry(key, &conditions)?;
+        }
+        map.end()
+    }
+}
+
+/// Reads whichever of the loaders' keys are there, meant to be flattened into a recipe
+impl<'de> Deserialize<'de> for Conditions {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut fields = BTreeMap::<String, Value>::deserialize(deserializer)?;
+        let (loader, conditions) = if let Some(conditions) = fields.remove("neoforge:conditions") {
+            (Loader::NeoForge, conditions)
+        } else if let Some(conditions) = fields.remove("conditions") {
+            (Loader::Forge, conditions)
+        } else {
+            return Ok(Conditions::default());
+        };
+        let conditions = conditions
+            .as_array()
+            .ok_or_else(|| D::Error::custom("conditions should be an array"))?
+            .iter()
+            .map(Condition::from_json)
+            .collect::<Result<_, _>>()
+            .map_err(D::Error::custom)?;
+        Ok(Conditions { loader, conditions })
+    }
+}
@@ -0,0 +1,117 @@
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{AlchemyGraph, BalanceThresholds, CostResolver, CraftingRecipe, Datapack, EstimateFormula, ItemRegistry, ItemTags, Loader, RecipeNaming, SplitSettings};
+
+/// Project settings shared by every tool, read from [`CONFIG_FILE`] in the working directory
+pub const CONFIG_FILE: &str = "datapack_generator.json";
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub naming: RecipeNaming,
+    /// Which loader's format to write NBT ingredients and load conditions in
+    pub loader: Loader,
+    /// Only load recipes when every mod they use is loaded
+    pub mod_loaded_conditions: bool,
+    /// Grist types from addons that aren't defined in this pack, `minestuck:` is added if there's no namespace
+    pub extra_grist: Vec<String>,
+    /// Where to find every item that exists, from registry dumps with an id on each line, mod jars or datapacks
+    pub item_registries: Vec<PathBuf>,
+    /// Mod jars or datapacks to read item tags from, tags from baselines and this pack are added after these
+    pub tag_sources: Vec<PathBuf>,
+    /// Datapacks loaded before ours, such as Minestuck's built in recipes extracted from its jar
+    pub baselines: Vec<PathBuf>,
+    /// Mod jars or datapacks with crafting, smelting and stonecutting recipes, such as the Minecraft jar
+    pub crafting_sources: Vec<PathBuf>,
+    /// How costs are estimated from crafting recipes
+    pub estimate: EstimateFormula,
+    /// How far off costs have to be for the balance report to flag them
+    pub balance: BalanceThresholds,
+    /// How `split_pack` divides the pack between mods
+    pub split: SplitSettings,
+}
+
+impl Config {
+    /// Loads the config at path, falling back to the defaults if it doesn't exist
+    pub fn load<P>(path: P) -> Config
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        match File::open(path) {
+            Ok(file) => serde_json::from_reader(file).unwrap_or_else(|e| {
+                eprintln!("Failed to parse config at {path:?}, {e}");
+                Config::default()
+            }),
+            Err(_) => Config::default(),
+        }
+    }
+
+    /// Loads every item registry source, reporting any that can't be read
+    pub fn load_item_registry(&self) -> ItemRegistry {
+        let mut registry = ItemRegistry::new();
+        for path in &self.item_registries {
+            if let Err(e) = registry.load(path) {
+                eprintln!("Failed to read items from {path:?}, {e}");
+            }
+        }
+        registry
+    }
+
+    /// Combines the item tags from every tag source, then the baselines, then the datapack
+    pub fn load_item_tags(&self, baselines: &[Datapack], datapack: &Datapack) -> ItemTags {
+        let mut tags = ItemTags::new();
+        for path in &self.tag_sources {
+            if let Err(e) = tags.load(path) {
+                eprintln!("Failed to read tags from {path:?}, {e}");
+            }
+        }
+        for baseline in baselines {
+            tags.add_datapack(baseline);
+        }
+        tags.add_datapack(datapack);
+        tags
+    }
+
+    /// Resolves grist costs from the baselines in order, then the datapack, named after the path it was loaded from
+    pub fn cost_resolver(&self, baselines: &[Datapack], datapack: &Datapack, datapack_name: &str) -> CostResolver {
+        let mut resolver = CostResolver::new(self.load_item_tags(baselines, datapack));
+        for (path, baseline) in self.baselines.iter().zip(baselines) {
+            resolver.add_pack(&path.to_string_lossy(), baseline);
+        }
+        resolver.add_pack(datapack_name, datapack);
+        resolver
+    }
+
+    /// Combinations from the baselines in order, then the datapack, with grist costs from [`Config::cost_resolver`]
+    pub fn alchemy_graph(&self, baselines: &[Datapack], datapack: &Datapack, datapack_name: &str) -> AlchemyGraph {
+        let mut graph = AlchemyGraph::new(self.cost_resolver(baselines, datapack, datapack_name));
+        for (path, baseline) in self.baselines.iter().zip(baselines) {
+            graph.add_pack(&path.to_string_lossy(), baseline);
+        }
+        graph.add_pack(datapack_name, datapack);
+        graph
+    }
+
+    /// Loads the recipes from every crafting source, reporting any that can't be read
+    pub fn load_crafting_recipes(&self) -> Vec<CraftingRecipe> {
+        let mut recipes = vec![];
+        for path in &self.crafting_sources {
+            match CraftingRecipe::load(path) {
+                Ok(loaded) => recipes.extend(loaded),
+                Err(e) => eprintln!("Failed to read recipes from {path:?}, {e}"),
+            }
+        }
+        recipes
+    }
+
+    /// Loads every baseline datapack
+    pub fn load_baselines(&self) -> Vec<Datapack> {
+        self.baselines.iter().map(Datapack::load).collect()
+    }
+}
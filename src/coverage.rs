@@ -0,0 +1,122 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use crate::{CostResolver, Ingredient};
+
+/// How many items from each mod have a grist cost
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    pub namespaces: BTreeMap<String, NamespaceCoverage>,
+}
+
+/// Grist cost coverage of the items from one mod
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NamespaceCoverage {
+    pub items: usize,
+    pub covered: usize,
+    pub uncovered: Vec<String>,
+    /// Covered, but only by recipes for tags and never for the item itself
+    pub tag_only: Vec<String>,
+    /// Covered by more than one recipe, so only the one with the highest priority is used
+    pub competing: Vec<String>,
+}
+
+impl NamespaceCoverage {
+    pub fn percent(&self) -> f32 {
+        if self.items == 0 {
+            100.0
+        } else {
+            self.covered as f32 * 100.0 / self.items as f32
+        }
+    }
+
+    fn add(&mut self, other: &NamespaceCoverage) {
+        self.items += other.items;
+        self.covered += other.covered;
+        self.uncovered.extend(other.uncovered.iter().cloned());
+        self.tag_only.extend(other.tag_only.iter().cloned());
+        self.competing.extend(other.competing.iter().cloned());
+    }
+}
+
+impl CoverageReport {
+    /// Checks which items have a grist cost out of every pack added to the resolver
+    pub fn new<'a>(resolver: &CostResolver, items: impl IntoIterator<Item = &'a str>) -> CoverageReport {
+        let mut report = CoverageReport::default();
+        for item in items {
+            let namespace = item.split_once(':').map_or("minecraft", |(namespace, _)| namespace);
+            let coverage = report.namespaces.entry(namespace.to_string()).or_default();
+            coverage.items += 1;
+            let resolution = resolver.resolve(&Ingredient::Item(item.to_string()));
+            if resolution.candidates.is_empty() {
+                coverage.uncovered.push(item.to_string());
+                continue;
+            }
+            coverage.covered += 1;
+            let direct = resolution
+                .candidates
+                .iter()
+                .any(|candidate| candidate.recipe.ingredient.item_ids().contains(&item));
+            if !direct {
+                coverage.tag_only.push(item.to_string());
+            }
+            if resolution.candidates.len() > 1 {
+                coverage.competing.push(item.to_string());
+            }
+        }
+        report
+    }
+
+    /// Every namespace added together
+    pub fn total(&self) -> NamespaceCoverage {
+        let mut total = NamespaceCoverage::default();
+        for coverage in self.namespaces.values() {
+            total.add(coverage);
+        }
+        total
+    }
+
+    /// The lists of items in each namespace, after the summary [`Display`] writes
+    pub fn details(&self) -> String {
+        let mut details = String::new();
+        for (namespace, coverage) in &self.namespaces {
+            for (heading, items) in [
+                ("no grist cost", &coverage.uncovered),
+                ("only covered by tags", &coverage.tag_only),
+                ("covered by competing recipes", &coverage.competing),
+            ] {
+                if !items.is_empty() {
+                    details.push_str(&format!("\n{namespace}, {heading}:\n"));
+                    for item in items {
+                        details.push_str(&format!("  {item}\n"));
+                    }
+                }
+            }
+        }
+        details
+    }
+}
+
+impl Display for NamespaceCoverage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/{} covered ({:.1}%), {} uncovered, {} only by tags, {} competing",
+            self.covered,
+            self.items,
+            self.percent(),
+            self.uncovered.len(),
+            self.tag_only.len(),
+            self.competing.len()
+        )
+    }
+}
+
+/// A line for each namespace and the total
+impl Display for CoverageReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (namespace, coverage) in &self.namespaces {
+            writeln!(f, "{namespace}: {coverage}")?;
+        }
+        write!(f, "total: {}", self.total())
+    }
+}
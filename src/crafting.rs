@@ -0,0 +1,168 @@
+use std::{
+    fmt::Display,
+    fs::File,
+    io::Read,
+    path::Path,
+};
+
+use serde_json::Value;
+use walkdir::WalkDir;
+
+use crate::Ingredient;
+
+/// Kinds of vanilla recipe that turn items into other items
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CraftingKind {
+    /// Shaped and shapeless crafting
+    Crafting,
+    /// Furnaces, blast furnaces, smokers and campfires
+    Smelting,
+    Stonecutting,
+}
+
+/// A vanilla style recipe from a mod jar or datapack, only what's needed to compare costs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CraftingRecipe {
+    /// Where in the jar or datapack it was found, without the .json extension
+    pub location: String,
+    pub kind: CraftingKind,
+    /// One entry for every item used, so a shaped recipe using an item in two slots lists it twice
+    pub inputs: Vec<Ingredient>,
+    pub output: String,
+    pub count: u32,
+}
+
+impl CraftingRecipe {
+    /// Reads a recipe, None for recipe types that aren't supported, such as special crafting recipes
+    pub fn from_json(location: &str, json: &Value) -> Option<CraftingRecipe> {
+        let recipe_type = json.get("type")?.as_str()?;
+        let kind = match recipe_type {
+            "minecraft:crafting_shaped" | "minecraft:crafting_shapeless" => CraftingKind::Crafting,
+            "minecraft:smelting" | "minecraft:blasting" | "minecraft:smoking" | "minecraft:campfire_cooking" => {
+                CraftingKind::Smelting
+            }
+            "minecraft:stonecutting" => CraftingKind::Stonecutting,
+            _ => return None,
+        };
+        let ingredient = |value: &Value| serde_json::from_value::<Ingredient>(value.clone()).ok();
+        let inputs = match recipe_type {
+            "minecraft:crafting_shaped" => {
+                let key = json.get("key")?.as_object()?;
+                let mut inputs = vec![];
+                for row in json.get("pattern")?.as_array()? {
+                    for symbol in row.as_str()?.chars().filter(|symbol| *symbol != ' ') {
+                        inputs.push(ingredient(key.get(&symbol.to_string())?)?);
+                    }
+                }
+                inputs
+            }
+            "minecraft:crafting_shapeless" => json
+                .get("ingredients")?
+                .as_array()?
+                .iter()
+                .map(ingredient)
+                .collect::<Option<_>>()?,
+            _ => vec![ingredient(json.get("ingredient")?)?],
+        };
+        //Results have been a plain id, or an object with "item" or from 1.20.5 "id", with the count beside it for
+        //stonecutting before 1.20.5
+        let (output, count) = match json.get("result")? {
+            Value::String(id) => (id.clone(), json.get("count").and_then(Value::as_u64).unwrap_or(1)),
+            Value::Object(result) => (
+                result.get("id").or_else(|| result.get("item"))?.as_str()?.to_string(),
+                result.get("count").and_then(Value::as_u64).unwrap_or(1),
+            ),
+            _ => return None,
+        };
+        Some(CraftingRecipe {
+            location: location.to_string(),
+            kind,
+            inputs,
+            output,
+            count: count as u32,
+        })
+    }
+
+    /// Every supported recipe in a mod jar, zipped datapack or datapack directory
+    ///
+    /// Recipes that can't be read or parsed are skipped with a warning
+    pub fn load<P>(path: P) -> std::io::Result<Vec<CraftingRecipe>>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let mut recipes = vec![];
+        let mut add = |location: String, json: std::io::Result<String>| {
+            let json = match json {
+                Ok(json) => json,
+                Err(e) => {
+                    eprintln!("Failed to read recipe {location}, {e}");
+                    return;
+                }
+            };
+            match serde_json::from_str::<Value>(&json) {
+                Ok(json) => recipes.extend(CraftingRecipe::from_json(&location, &json)),
+                Err(e) => eprintln!("Failed to parse recipe {location}, {e}"),
+            }
+        };
+        if path.is_dir() {
+            for dir_entry in WalkDir::new(path.join("data")) {
+                let dir_entry = match dir_entry {
+                    Ok(dir_entry) => dir_entry,
+                    Err(e) => {
+                        eprintln!("Failed to read {e}");
+                        continue;
+                    }
+                };
+                let file_path = dir_entry.path();
+                if file_path.extension().is_some_and(|e| e == "json") {
+                    let location = file_path
+                        .strip_prefix(path)
+                        .unwrap()
+                        .with_extension("")
+                        .to_string_lossy()
+                        .replace('\\', "/");
+                    if is_recipe_location(&location) {
+                        add(location, std::fs::read_to_string(file_path));
+                    }
+                }
+            }
+        } else {
+            let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+            //Jars have lots of other json, such as models and lang files, which don't need to be read
+            let names: Vec<String> = archive
+                .file_names()
+                .filter(|name| name.strip_suffix(".json").is_some_and(is_recipe_location))
+                .map(str::to_string)
+                .collect();
+            for name in names {
+                let mut json = String::new();
+                let read = archive
+                    .by_name(&name)
+                    .map_err(std::io::Error::from)
+                    .and_then(|mut file| file.read_to_string(&mut json))
+                    .map(|_| json);
+                add(name.strip_suffix(".json").unwrap().to_string(), read);
+            }
+        }
+        Ok(recipes)
+    }
+}
+
+/// Whether a location is in a recipe directory, `recipes` before 1.21 and `recipe` after
+fn is_recipe_location(location: &str) -> bool {
+    location
+        .strip_prefix("data/")
+        .and_then(|rest| rest.split_once('/'))
+        .is_some_and(|(_, rest)| rest.starts_with("recipes/") || rest.starts_with("recipe/"))
+}
+
+impl Display for CraftingKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CraftingKind::Crafting => write!(f, "craft"),
+            CraftingKind::Smelting => write!(f, "smelt"),
+            CraftingKind::Stonecutting => write!(f, "stonecut"),
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    str::FromStr,
+};
+
+use crate::{AlchemyEdge, AlchemyGraph};
+
+/// File formats an [`AlchemyGraph`] can be drawn in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagramFormat {
+    /// Graphviz
+    Dot,
+    GraphMl,
+    Mermaid,
+}
+
+impl FromStr for DiagramFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "dot" | "gv" => Ok(DiagramFormat::Dot),
+            "graphml" => Ok(DiagramFormat::GraphMl),
+            "mermaid" | "mmd" => Ok(DiagramFormat::Mermaid),
+            _ => Err(format!("Unknown diagram format \"{s}\", expected dot, graphml or mermaid")),
+        }
+    }
+}
+
+/// Which combinations to draw, everything by default
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiagramFilter {
+    /// Only the combinations that go into making this item
+    pub root: Option<String>,
+    /// Only combinations whose output is in this namespace
+    pub namespace: Option<String>,
+    /// How many combinations back from the root to go, only used with a root
+    pub max_depth: Option<usize>,
+}
+
+impl DiagramFilter {
+    /// The combinations in the graph that pass the filter
+    ///
+    /// The namespace only decides which of the combinations found from the root are kept, so chains passing through
+    /// other mods' items are still followed
+    pub fn edges<'a>(&self, graph: &'a AlchemyGraph) -> Vec<&'a AlchemyEdge> {
+        let in_namespace = |edge: &&AlchemyEdge| {
+            self.namespace.as_deref().is_none_or(|namespace| {
+                edge.output.split_once(':').is_some_and(|(output_namespace, _)| output_namespace == namespace)
+            })
+        };
+        let Some(root) = &self.root else {
+            return graph.edges().filter(in_namespace).collect();
+        };
+        //Breadth first from the root to its inputs, so each combination is found at its smallest depth
+        let mut found: BTreeMap<&str, &AlchemyEdge> = BTreeMap::new();
+        let mut visited: BTreeSet<&str> = BTreeSet::from([root.as_str()]);
+        let mut current: Vec<&str> = vec![root];
+        let mut depth = 0;
+        while !current.is_empty() && self.max_depth.is_none_or(|max_depth| depth < max_depth) {
+            let mut next = vec![];
+            for item in current {
+                for edge in graph.recipes_for(item) {
+                    found.insert(&edge.location, edge);
+                    for input in &edge.inputs {
+                        if visited.insert(input) {
+                            next.push(input.as_str());
+                        }
+                    }
+                }
+            }
+            current = next;
+            depth += 1;
+        }
+        found.into_values().filter(in_namespace).collect()
+    }
+}
+
+impl DiagramFormat {
+    /// Draws the combinations, with an edge from each input to the output labeled with the mode
+    pub fn write(&self, edges: &[&AlchemyEdge]) -> String {
+        let nodes: BTreeSet<&str> = edges
+            .iter()
+            .flat_map(|edge| [edge.inputs[0].as_str(), edge.inputs[1].as_str(), edge.output.as_str()])
+            .collect();
+        //Names can have characters none of the formats allow in ids, such as NBT, so nodes are numbered
+        let ids: BTreeMap<&str, String> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (*node, format!("n{i}")))
+            .collect();
+        let mut out = String::new();
+        match self {
+            DiagramFormat::Dot => {
+                out.push_str("digraph alchemy {\n    rankdir=LR;\n");
+                for (node, id) in &ids {
+                    writeln!(out, "    {id} [label=\"{}\"];", escape_dot(node)).unwrap();
+                }
+                for edge in edges {
+                    for input in &edge.inputs {
+                        writeln!(
+                            out,
+                            "    {} -> {} [label=\"{}\", tooltip=\"{}\"];",
+                            ids[input.as_str()],
+                            ids[edge.output.as_str()],
+                            edge.mode().symbol(),
+                            escape_dot(&edge.location)
+                        )
+                        .unwrap();
+                    }
+                }
+                out.push_str("}\n");
+            }
+            DiagramFormat::GraphMl => {
+                out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+                out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+                out.push_str("  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n");
+                out.push_str("  <key id=\"mode\" for=\"edge\" attr.name=\"mode\" attr.type=\"string\"/>\n");
+                out.push_str("  <key id=\"recipe\" for=\"edge\" attr.name=\"recipe\" attr.type=\"string\"/>\n");
+                out.push_str("  <graph id=\"alchemy\" edgedefault=\"directed\">\n");
+                for (node, id) in &ids {
+                    writeln!(out, "    <node id=\"{id}\"><data key=\"label\">{}</data></node>", escape_xml(node))
+                        .unwrap();
+                }
+                for edge in edges {
+                    for input in &edge.inputs {
+                        writeln!(
+                            out,
+                            "    <edge source=\"{}\" target=\"{}\"><data key=\"mode\">{}</data><data key=\"recipe\">{}</data></edge>",
+                            ids[input.as_str()],
+                            ids[edge.output.as_str()],
+                            escape_xml(edge.mode().symbol()),
+                            escape_xml(&edge.location)
+                        )
+                        .unwrap();
+                    }
+                }
+                out.push_str("  </graph>\n</graphml>\n");
+            }
+            DiagramFormat::Mermaid => {
+                out.push_str("flowchart LR\n");
+                for (node, id) in &ids {
+                    writeln!(out, "    {id}[\"{}\"]", escape_mermaid(node)).unwrap();
+                }
+                for edge in edges {
+                    for input in &edge.inputs {
+                        writeln!(
+                            out,
+                            "    {} -- \"{}\" --> {}",
+                            ids[input.as_str()],
+                            escape_mermaid(edge.mode().symbol()),
+                            ids[edge.output.as_str()]
+                        )
+                        .unwrap();
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+impl AlchemyGraph {
+    /// Draws the combinations that pass the filter
+    pub fn diagram(&self, format: DiagramFormat, filter: &DiagramFilter) -> String {
+        format.write(&filter.edges(self))
+    }
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn escape_mermaid(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
@@ -0,0 +1,478 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    str::FromStr,
+};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{Datapack, GristType, MCMeta, Recipe, ResultItem, Tag};
+
+/// How a recipe, grist type or tag differs between two packs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+    /// At a different location, and possibly changed too
+    Moved,
+}
+
+/// One thing that differs between two packs, with the old and new versions
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change<T> {
+    /// The item a grist cost is for, the output of a combination, or the id of a grist type or tag
+    pub name: String,
+    pub old_location: Option<String>,
+    pub new_location: Option<String>,
+    pub old: Option<T>,
+    pub new: Option<T>,
+    /// What changed, like `build 2 → 3` or `+ruby 1`
+    pub details: Vec<String>,
+}
+
+/// Everything that differs between two packs, ignoring formatting and the order of things in files
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DatapackDiff {
+    pub recipes: Vec<Change<Recipe>>,
+    pub grist_types: Vec<Change<GristType>>,
+    pub item_tags: Vec<Change<Tag>>,
+    /// pack.mcmeta, if its format, description or filter hiding recipes from other packs changed
+    pub mcmeta: Vec<Change<MCMeta>>,
+}
+
+/// Formats a [`DatapackDiff`] can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffFormat {
+    Text,
+    /// For pull request comments
+    Markdown,
+    Json,
+}
+
+impl FromStr for DiffFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" | "txt" => Ok(DiffFormat::Text),
+            "markdown" | "md" => Ok(DiffFormat::Markdown),
+            "json" => Ok(DiffFormat::Json),
+            _ => Err(format!("Unknown diff format \"{s}\", expected text, markdown or json")),
+        }
+    }
+}
+
+impl<T> Change<T> {
+    pub fn kind(&self) -> ChangeKind {
+        match (&self.old_location, &self.new_location) {
+            (None, _) => ChangeKind::Added,
+            (_, None) => ChangeKind::Removed,
+            (old, new) if old != new => ChangeKind::Moved,
+            _ => ChangeKind::Changed,
+        }
+    }
+
+    fn summary(&self) -> String {
+        let mut details = self.details.clone();
+        match self.kind() {
+            ChangeKind::Added => details.insert(0, "added".into()),
+            ChangeKind::Removed => details.insert(0, "removed".into()),
+            ChangeKind::Moved => details.insert(
+                0,
+                format!(
+                    "moved from {} to {}",
+                    self.old_location.as_deref().unwrap_or_default(),
+                    self.new_location.as_deref().unwrap_or_default()
+                ),
+            ),
+            ChangeKind::Changed => {}
+        }
+        format!("{}: {}", self.name, details.join(", "))
+    }
+
+    fn location(&self) -> &str {
+        self.new_location.as_deref().or(self.old_location.as_deref()).unwrap_or_default()
+    }
+}
+
+fn short_grist(grist: &str) -> &str {
+    grist.strip_prefix("minestuck:").unwrap_or(grist)
+}
+
+/// What a recipe is listed as, the same for a recipe before and after it changes
+fn recipe_name(recipe: &Recipe) -> String {
+    match recipe {
+        Recipe::GristCost(recipe) => recipe.ingredient.to_string(),
+        Recipe::Combination(recipe) => format!("{} {} {}", recipe.input1, recipe.mode.symbol(), recipe.input2),
+    }
+}
+
+fn priority_name(priority: Option<i32>) -> String {
+    priority.map(|priority| priority.to_string()).unwrap_or_else(|| "default".into())
+}
+
+/// The contents of a recipe, for ones that were added or removed
+fn recipe_details(recipe: &Recipe) -> Vec<String> {
+    match recipe {
+        Recipe::GristCost(recipe) => recipe
+            .grist_cost
+            .iter()
+            .map(|(grist, amount)| format!("{} {amount}", short_grist(grist)))
+            .chain([format!("priority {}", priority_name(recipe.priority))])
+            .collect(),
+        Recipe::Combination(recipe) => {
+            let ResultItem::Item(output) = &recipe.output;
+            vec![format!("makes {output}")]
+        }
+    }
+}
+
+/// What differs between two versions of a recipe
+fn recipe_changes(old: &Recipe, new: &Recipe) -> Vec<String> {
+    let mut details = vec![];
+    match (old, new) {
+        (Recipe::GristCost(old), Recipe::GristCost(new)) => {
+            let grist: BTreeSet<&String> = old.grist_cost.keys().chain(new.grist_cost.keys()).collect();
+            for grist in grist {
+                match (old.grist_cost.get(grist), new.grist_cost.get(grist)) {
+                    (Some(old), Some(new)) if old != new => details.push(format!("{} {old} → {new}", short_grist(grist))),
+                    (Some(old), None) => details.push(format!("-{} {old}", short_grist(grist))),
+                    (None, Some(new)) => details.push(format!("+{} {new}", short_grist(grist))),
+                    _ => {}
+                }
+            }
+            if old.priority != new.priority {
+                details.push(format!("priority {} → {}", priority_name(old.priority), priority_name(new.priority)));
+            }
+            if old.ingredient != new.ingredient {
+                details.push(format!("ingredient {} → {}", old.ingredient, new.ingredient));
+            }
+        }
+        (Recipe::Combination(old), Recipe::Combination(new)) => {
+            let (ResultItem::Item(old_output), ResultItem::Item(new_output)) = (&old.output, &new.output);
+            if old_output != new_output {
+                details.push(format!("output {old_output} → {new_output}"));
+            }
+            if old.mode != new.mode {
+                details.push(format!("mode {} → {}", old.mode.symbol(), new.mode.symbol()));
+            }
+        }
+        _ => details.push(format!("{old} → {new}")),
+    }
+    if old.conditions() != new.conditions() {
+        details.push("conditions changed".into());
+    }
+    details
+}
+
+/// Top level fields that differ between two json objects, for anything without its own description
+fn field_changes<T: Serialize>(old: &T, new: &T) -> Vec<String> {
+    let (Value::Object(old), Value::Object(new)) = (json!(old), json!(new)) else {
+        return vec!["changed".into()];
+    };
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    keys.into_iter()
+        .filter_map(|key| match (old.get(key), new.get(key)) {
+            (Some(old), Some(new)) if old != new => Some(format!("{key} {old} → {new}")),
+            (Some(old), None) => Some(format!("-{key} {old}")),
+            (None, Some(new)) => Some(format!("+{key} {new}")),
+            _ => None,
+        })
+        .collect()
+}
+
+/// What differs between two pack.mcmetas, with each filter block added or removed
+fn mcmeta_changes(old: &MCMeta, new: &MCMeta) -> Vec<String> {
+    let blocks = |mcmeta: &MCMeta| -> BTreeSet<String> {
+        mcmeta.filter.iter().flat_map(|filter| &filter.block).map(|block| json!(block).to_string()).collect()
+    };
+    let (old_blocks, new_blocks) = (blocks(old), blocks(new));
+    let mut details = field_changes(&old.pack, &new.pack);
+    details.extend(new_blocks.difference(&old_blocks).map(|block| format!("+filter {block}")));
+    details.extend(old_blocks.difference(&new_blocks).map(|block| format!("-filter {block}")));
+    details.extend(field_changes(&old.extra, &new.extra));
+    details
+}
+
+fn tag_changes(old: &Tag, new: &Tag) -> Vec<String> {
+    let entries = |tag: &Tag| -> BTreeSet<String> {
+        tag.values
+            .iter()
+            .map(|entry| match json!(entry) {
+                Value::String(entry) => entry,
+                entry => entry.to_string(),
+            })
+            .collect()
+    };
+    let (old_entries, new_entries) = (entries(old), entries(new));
+    let mut details: Vec<String> = new_entries
+        .difference(&old_entries)
+        .map(|entry| format!("+{entry}"))
+        .chain(old_entries.difference(&new_entries).map(|entry| format!("-{entry}")))
+        .collect();
+    if old.replace != new.replace {
+        details.push(format!("replace {} → {}", old.replace, new.replace));
+    }
+    details
+}
+
+/// Pairs things up by location, then what's left by name, so moved files are found
+fn diff_maps<T: Clone + PartialEq>(
+    old: &BTreeMap<String, T>,
+    new: &BTreeMap<String, T>,
+    name: impl Fn(&str, &T) -> String,
+    changes: impl Fn(&T, &T) -> Vec<String>,
+    contents: impl Fn(&T) -> Vec<String>,
+) -> Vec<Change<T>> {
+    let mut found = vec![];
+    let mut removed: BTreeMap<String, Vec<(&String, &T)>> = BTreeMap::new();
+    for (location, old_value) in old {
+        match new.get(location) {
+            Some(new_value) => {
+                //Nothing to describe if only the order of something like a tag's entries changed
+                let details = changes(old_value, new_value);
+                if old_value != new_value && !details.is_empty() {
+                    found.push(Change {
+                        name: name(location, new_value),
+                        old_location: Some(location.clone()),
+                        new_location: Some(location.clone()),
+                        old: Some(old_value.clone()),
+                        new: Some(new_value.clone()),
+                        details,
+                    });
+                }
+            }
+            None => removed.entry(name(location, old_value)).or_default().push((location, old_value)),
+        }
+    }
+    for (location, new_value) in new.iter().filter(|(location, _)| !old.contains_key(*location)) {
+        let name = name(location, new_value);
+        let moved_from = removed.get_mut(&name).and_then(|removed| removed.pop());
+        found.push(match moved_from {
+            Some((old_location, old_value)) => Change {
+                details: changes(old_value, new_value),
+                name,
+                old_location: Some(old_location.clone()),
+                new_location: Some(location.clone()),
+                old: Some(old_value.clone()),
+                new: Some(new_value.clone()),
+            },
+            None => Change {
+                details: contents(new_value),
+                name,
+                old_location: None,
+                new_location: Some(location.clone()),
+                old: None,
+                new: Some(new_value.clone()),
+            },
+        });
+    }
+    for (name, removed) in removed {
+        for (location, old_value) in removed {
+            found.push(Change {
+                name: name.clone(),
+                old_location: Some(location.clone()),
+                new_location: None,
+                old: Some(old_value.clone()),
+                new: None,
+                details: contents(old_value),
+            });
+        }
+    }
+    found.sort_by(|a, b| (&a.name, a.location()).cmp(&(&b.name, b.location())));
+    found
+}
+
+impl Datapack {
+    /// What changed going from this pack to another
+    pub fn diff(&self, other: &Datapack) -> DatapackDiff {
+        DatapackDiff {
+            recipes: diff_maps(&self.recipes, &other.recipes, |_, recipe| recipe_name(recipe), recipe_changes, recipe_details),
+            grist_types: diff_maps(
+                &self.grist_types,
+                &other.grist_types,
+                |id, _| id.to_string(),
+                field_changes,
+                |_| vec![],
+            ),
+            item_tags: diff_maps(&self.item_tags, &other.item_tags, |id, _| id.to_string(), tag_changes, |_| vec![]),
+            mcmeta: diff_maps(
+                &BTreeMap::from([("pack.mcmeta".to_string(), self.mcmeta.clone())]),
+                &BTreeMap::from([("pack.mcmeta".to_string(), other.mcmeta.clone())]),
+                |location, _| location.to_string(),
+                mcmeta_changes,
+                |_| vec![],
+            ),
+        }
+    }
+}
+
+impl DatapackDiff {
+    pub fn is_empty(&self) -> bool {
+        self.recipes.is_empty() && self.grist_types.is_empty() && self.item_tags.is_empty() && self.mcmeta.is_empty()
+    }
+
+    /// How many recipes were added, removed, changed and moved, like `2 added, 1 changed`
+    pub fn summary(&self) -> String {
+        let mut counts: BTreeMap<ChangeKind, usize> = BTreeMap::new();
+        for change in &self.recipes {
+            *counts.entry(change.kind()).or_default() += 1;
+        }
+        let counts: Vec<String> = counts
+            .into_iter()
+            .map(|(kind, count)| format!("{count} {}", format!("{kind:?}").to_lowercase()))
+            .collect();
+        if counts.is_empty() {
+            "No recipe changes".into()
+        } else {
+            format!("Recipes: {}", counts.join(", "))
+        }
+    }
+
+    pub fn write(&self, format: DiffFormat) -> String {
+        let mut out = String::new();
+        let sections = [
+            ("Recipes", self.recipes.iter().map(|c| (c.kind(), c.summary(), c.location())).collect::<Vec<_>>()),
+            ("Grist types", self.grist_types.iter().map(|c| (c.kind(), c.summary(), c.location())).collect()),
+            ("Item tags", self.item_tags.iter().map(|c| (c.kind(), c.summary(), c.location())).collect()),
+            ("Pack", self.mcmeta.iter().map(|c| (c.kind(), c.summary(), c.location())).collect()),
+        ];
+        match format {
+            DiffFormat::Text => {
+                writeln!(out, "{}", self.summary()).unwrap();
+                for (title, changes) in sections.iter().filter(|(_, changes)| !changes.is_empty()) {
+                    writeln!(out, "\n{title}:").unwrap();
+                    for (kind, summary, _) in changes {
+                        let symbol = match kind {
+                            ChangeKind::Added => '+',
+                            ChangeKind::Removed => '-',
+                            ChangeKind::Changed => '~',
+                            ChangeKind::Moved => '>',
+                        };
+                        writeln!(out, "{symbol} {summary}").unwrap();
+                    }
+                }
+            }
+            DiffFormat::Markdown => {
+                writeln!(out, "**{}**", self.summary()).unwrap();
+                for (title, changes) in sections.iter().filter(|(_, changes)| !changes.is_empty()) {
+                    writeln!(out, "\n### {title}\n").unwrap();
+                    for (_, summary, location) in changes {
+                        let (name, details) = summary.split_once(": ").unwrap_or((summary, ""));
+                        //Grist types, tags and pack.mcmeta are already named by where they are
+                        if name == *location {
+                            writeln!(out, "- **`{name}`**: {details}").unwrap();
+                        } else {
+                            writeln!(out, "- **`{name}`**: {details} (`{location}`)").unwrap();
+                        }
+                    }
+                }
+            }
+            DiffFormat::Json => {
+                let changes = |changes: Vec<Value>| Value::Array(changes);
+                let value = json!({
+                    "recipes": changes(self.recipes.iter().map(Change::to_json).collect()),
+                    "grist_types": changes(self.grist_types.iter().map(Change::to_json).collect()),
+                    "item_tags": changes(self.item_tags.iter().map(Change::to_json).collect()),
+                    "mcmeta": changes(self.mcmeta.iter().map(Change::to_json).collect()),
+                });
+                out = serde_json::to_string_pretty(&value).unwrap();
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+impl<T: Serialize> Change<T> {
+    fn to_json(&self) -> Value {
+        json!({
+            "kind": format!("{:?}", self.kind()).to_lowercase(),
+            "name": self.name,
+            "old_location": self.old_location,
+            "new_location": self.new_location,
+            "details": self.details,
+            "old": self.old,
+            "new": self.new,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Filter, FilterBlock, GristCostRecipe, Ingredient};
+
+    fn grist_cost(grist: &[(&str, i32)]) -> Recipe {
+        GristCostRecipe {
+            priority: Some(100),
+            ingredient: Ingredient::Item("minecraft:stone".to_string()),
+            grist_cost: grist.iter().map(|(grist, amount)| (format!("minestuck:{grist}"), *amount)).collect(),
+            conditions: Default::default(),
+        }
+        .into()
+    }
+
+    fn pack(location: &str, recipe: Recipe) -> Datapack {
+        let mut datapack = Datapack::new();
+        datapack.recipes.insert(location.to_string(), recipe);
+        datapack
+    }
+
+    const STONE: &str = "data/minestuck/recipes/grist_costs/minecraft/stone";
+
+    #[test]
+    fn changed_grist() {
+        let old = pack(STONE, grist_cost(&[("build", 2)]));
+        let new = pack(STONE, grist_cost(&[("build", 3), ("ruby", 1)]));
+        let diff = old.diff(&new);
+        assert_eq!(diff.recipes.len(), 1);
+        assert_eq!(diff.recipes[0].kind(), ChangeKind::Changed);
+        assert_eq!(diff.recipes[0].summary(), "minecraft:stone: build 2 → 3, +ruby 1");
+        assert_eq!(diff.summary(), "Recipes: 1 changed");
+        assert_eq!(
+            diff.write(DiffFormat::Text),
+            "Recipes: 1 changed\n\nRecipes:\n~ minecraft:stone: build 2 → 3, +ruby 1\n"
+        );
+    }
+
+    #[test]
+    fn moved_recipe() {
+        let old = pack(STONE, grist_cost(&[("build", 2)]));
+        let new = pack("data/minestuck/recipes/grist_costs/stone", grist_cost(&[("build", 2)]));
+        let diff = old.diff(&new);
+        assert_eq!(diff.recipes.len(), 1);
+        assert_eq!(diff.recipes[0].kind(), ChangeKind::Moved);
+        assert_eq!(
+            diff.recipes[0].summary(),
+            format!("minecraft:stone: moved from {STONE} to data/minestuck/recipes/grist_costs/stone")
+        );
+    }
+
+    #[test]
+    fn unchanged() {
+        let old = pack(STONE, grist_cost(&[("build", 2)]));
+        assert!(old.diff(&old.clone()).is_empty());
+    }
+
+    #[test]
+    fn mcmeta() {
+        let old = Datapack::new();
+        let mut new = Datapack::new();
+        new.mcmeta.pack.pack_format = 15;
+        new.mcmeta.filter = Some(Filter {
+            block: vec![FilterBlock {
+                namespace: Some("minestuck".to_string()),
+                path: None,
+            }],
+        });
+        let diff = old.diff(&new);
+        assert!(!diff.is_empty());
+        assert_eq!(
+            diff.mcmeta[0].summary(),
+            r#"pack.mcmeta: pack_format 10 → 15, +filter {"namespace":"minestuck"}"#
+        );
+    }
+}
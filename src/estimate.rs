@@ -0,0 +1,210 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    add_grist, format_grist, grist_resource, grist_total, solver::check_amounts, CostResolver, CraftingKind, CraftingRecipe, GristCostRecipe,
+    GristTotals, Ingredient, ItemTags, GENERATED_PRIORITY,
+};
+
+/// How estimated costs are worked out from the costs of a recipe's inputs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EstimateFormula {
+    /// What the inputs' cost is multiplied by for each kind of recipe, before dividing by the output count
+    pub crafting: f32,
+    pub smelting: f32,
+    pub stonecutting: f32,
+    /// Grist added to smelted items for the fuel, `minestuck:` is added if there's no namespace
+    pub smelting_extra: BTreeMap<String, i64>,
+    pub rounding: Rounding,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rounding {
+    /// Costs never round down to nothing
+    #[default]
+    Up,
+    Down,
+    Nearest,
+}
+
+impl Default for EstimateFormula {
+    fn default() -> Self {
+        Self {
+            crafting: 1.0,
+            smelting: 1.0,
+            stonecutting: 1.0,
+            smelting_extra: BTreeMap::new(),
+            rounding: Rounding::Up,
+        }
+    }
+}
+
+impl EstimateFormula {
+    /// The cost of one output from what the inputs cost together
+    pub fn apply(&self, kind: CraftingKind, inputs: &GristTotals, count: u32) -> GristTotals {
+        let multiplier = match kind {
+            CraftingKind::Crafting => self.crafting,
+            CraftingKind::Smelting => self.smelting,
+            CraftingKind::Stonecutting => self.stonecutting,
+        } as f64;
+        let mut total = inputs.clone();
+        if kind == CraftingKind::Smelting {
+            let extra = self
+                .smelting_extra
+                .iter()
+                .map(|(grist, amount)| (grist_resource(grist), *amount))
+                .collect();
+            add_grist(&mut total, &extra);
+        }
+        total
+            .into_iter()
+            .map(|(grist, amount)| {
+                let amount = amount as f64 * multiplier / count.max(1) as f64;
+                let amount = match self.rounding {
+                    Rounding::Up => amount.ceil(),
+                    Rounding::Down => amount.floor(),
+                    Rounding::Nearest => amount.round(),
+                };
+                (grist, amount as i64)
+            })
+            //Amounts below nothing from a negative multiplier are left for the draft to report
+            .filter(|(_, amount)| *amount != 0)
+            .collect()
+    }
+}
+
+/// A proposed grist cost for an item without one, worked out from a recipe making it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DraftCost {
+    pub item: String,
+    pub grist: GristTotals,
+    /// Location of the recipe it was worked out from
+    pub source: String,
+    pub kind: CraftingKind,
+}
+
+impl DraftCost {
+    /// The grist cost recipe to add if the draft is accepted, an error if the amounts can't be written in one
+    pub fn recipe(&self) -> Result<GristCostRecipe, String> {
+        Ok(GristCostRecipe {
+            priority: Some(GENERATED_PRIORITY),
+            ingredient: Ingredient::Item(self.item.clone()),
+            grist_cost: check_amounts(&self.item, &self.grist)?,
+            conditions: Default::default(),
+        })
+    }
+}
+
+/// The cost of one of a recipe's output with the formula, None if an input has no cost or it comes to nothing
+///
+/// Ingredients matching several items use the cheapest
+pub fn recipe_cost(
+    recipe: &CraftingRecipe,
+    formula: &EstimateFormula,
+    tags: &ItemTags,
+    mut cost: impl FnMut(&str) -> Option<GristTotals>,
+) -> Option<GristTotals> {
+    let mut inputs = GristTotals::new();
+    for ingredient in &recipe.inputs {
+        let cheapest = tags
+            .ingredient_items(ingredient)
+            .ok()
+            .into_iter()
+            .flatten()
+            .filter_map(|item| cost(&item))
+            .min_by_key(grist_total)?;
+        add_grist(&mut inputs, &cheapest);
+    }
+    Some(formula.apply(recipe.kind, &inputs, recipe.count)).filter(|grist| !grist.is_empty())
+}
+
+/// Proposes costs for items without one from recipes making them, using the cheapest recipe if there are several
+///
+/// Costs go through chains of recipes, so an item made from an item that only has an estimated cost still gets
+/// one. See [`recipe_cost`] for a single recipe
+pub fn estimate_costs(resolver: &CostResolver, recipes: &[CraftingRecipe], formula: &EstimateFormula) -> Vec<DraftCost> {
+    let tags = resolver.tags();
+    let mut known: BTreeMap<String, Option<GristTotals>> = BTreeMap::new();
+    let mut drafts: BTreeMap<String, DraftCost> = BTreeMap::new();
+    let mut explicit = |item: &str| -> Option<GristTotals> {
+        known
+            .entry(item.to_string())
+            .or_insert_with(|| resolver.item_cost(item).map(|(grist, _)| grist))
+            .clone()
+    };
+    let uncovered: Vec<&CraftingRecipe> = recipes
+        .iter()
+        .filter(|recipe| explicit(&recipe.output).is_none())
+        .collect();
+    //Every pass that changes something makes at least one estimate final, so there can't be more passes than recipes
+    for _ in 0..=uncovered.len() {
+        let mut changed = false;
+        for recipe in &uncovered {
+            let cost = |item: &str| explicit(item).or_else(|| drafts.get(item).map(|draft| draft.grist.clone()));
+            let Some(grist) = recipe_cost(recipe, formula, tags, cost) else {
+                continue;
+            };
+            let cheaper = drafts
+                .get(&recipe.output)
+                .is_none_or(|draft| grist_total(&grist) < grist_total(&draft.grist));
+            if cheaper {
+                let draft = DraftCost {
+                    item: recipe.output.clone(),
+                    grist,
+                    source: recipe.location.clone(),
+                    kind: recipe.kind,
+                };
+                drafts.insert(recipe.output.clone(), draft);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    drafts.into_values().collect()
+}
+
+impl Display for DraftCost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} costs {}, from {} ({})", self.item, format_grist(&self.grist), self.source, self.kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft(amount: i64) -> DraftCost {
+        DraftCost {
+            item: "test:ingot".to_string(),
+            grist: GristTotals::from([("minestuck:build".to_string(), amount)]),
+            source: "test:ingot_from_ore".to_string(),
+            kind: CraftingKind::Smelting,
+        }
+    }
+
+    #[test]
+    fn draft_recipes() {
+        let recipe = draft(4).recipe().unwrap();
+        assert_eq!(recipe.grist_cost, BTreeMap::from([("minestuck:build".to_string(), 4)]));
+        assert!(draft(-4).recipe().unwrap_err().contains("less than nothing"));
+        assert!(draft(i64::MAX).recipe().unwrap_err().contains("more than a recipe can hold"));
+    }
+
+    #[test]
+    fn negative_multiplier_is_kept_to_report() {
+        let formula = EstimateFormula {
+            smelting: -1.0,
+            ..Default::default()
+        };
+        let inputs = GristTotals::from([("minestuck:build".to_string(), 2)]);
+        assert_eq!(
+            formula.apply(CraftingKind::Smelting, &inputs, 1),
+            GristTotals::from([("minestuck:build".to_string(), -2)])
+        );
+    }
+}
@@ -0,0 +1,290 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
+
+use crate::{add_grist, format_grist, grist_total, AlchemyGraph, CraftingKind, CraftingRecipe, GristTotals, Ingredient};
+
+/// Most recipes in a row looked at through items without a grist cost
+const MAX_STEPS: usize = 4;
+
+/// An item going into or coming out of a recipe, priced by its grist cost
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PricedItem {
+    pub item: String,
+    pub count: i64,
+    /// For a single item
+    pub cost: GristTotals,
+    /// The grist cost recipe the cost comes from
+    pub cost_location: String,
+}
+
+impl PricedItem {
+    fn total(&self) -> GristTotals {
+        self.cost.iter().map(|(grist, amount)| (grist.clone(), amount * self.count)).collect()
+    }
+
+    fn times(&self, times: i64) -> PricedItem {
+        PricedItem {
+            count: self.count * times,
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ExploitKind {
+    /// A crafting, smelting or stonecutting recipe turning its inputs into its output
+    Recipe(CraftingKind),
+    /// A combination, counted as trading its output for its inputs since an output that costs less than them is
+    /// worth alchemizing in their place
+    Combination,
+}
+
+/// One recipe on the way round an exploit, turning the item carried from the step before into another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExploitStep {
+    pub kind: ExploitKind,
+    /// Location of the crafting or combination recipe
+    pub recipe: String,
+    /// The item carried in and how many of it each use of the recipe takes
+    pub from: String,
+    pub consumed: i64,
+    /// The item carried on and how many of it each use of the recipe makes
+    pub to: String,
+    pub produced: i64,
+    /// Other items each use takes, alchemized for their grist cost
+    pub bought: Vec<PricedItem>,
+    /// Other items each use makes, turned back into grist
+    pub sold: Vec<PricedItem>,
+    /// How many times the recipe is used going round once
+    pub times: i64,
+}
+
+/// A chain of recipes that makes grist out of nothing, starting and ending with grist
+///
+/// An item is alchemized, carried through the recipes and what comes out the end is turned back into grist
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exploit {
+    /// The item alchemized at the start, as many as going round once takes
+    pub start: PricedItem,
+    pub steps: Vec<ExploitStep>,
+    /// The item made at the end
+    pub end: PricedItem,
+    /// Grist gained every time round the loop, never negative
+    pub gain: GristTotals,
+}
+
+/// The difference between two sets of grist if the first has at least as much of every type and more of some
+fn gain(more: &GristTotals, less: &GristTotals) -> Option<GristTotals> {
+    let mut difference = more.clone();
+    for (grist, amount) in less {
+        *difference.entry(grist.clone()).or_default() -= amount;
+    }
+    difference.retain(|_, amount| *amount != 0);
+    (!difference.is_empty() && difference.values().all(|amount| *amount > 0)).then_some(difference)
+}
+
+/// Works out how many of everything going round once takes, so every recipe is used a whole number of times, and
+/// the grist gained if there is any
+fn close(start: &PricedItem, steps: &[ExploitStep], end: &PricedItem) -> Option<Exploit> {
+    let mut units = steps.iter().try_fold(1i64, |units, step| units.checked_mul(step.consumed))?;
+    let start = start.times(units);
+    let mut spent = start.total();
+    let mut made = GristTotals::new();
+    let mut steps = steps.to_vec();
+    for step in &mut steps {
+        step.times = units / step.consumed;
+        units = step.times.checked_mul(step.produced)?;
+        for item in &step.bought {
+            add_grist(&mut spent, &item.times(step.times).total());
+        }
+        for item in &step.sold {
+            add_grist(&mut made, &item.times(step.times).total());
+        }
+    }
+    let end = end.times(units);
+    add_grist(&mut made, &end.total());
+    let gain = gain(&made, &spent)?;
+    Some(Exploit {
+        start,
+        steps,
+        end,
+        gain,
+    })
+}
+
+impl AlchemyGraph {
+    /// Chains of crafting recipes and combinations that make more grist than they use
+    ///
+    /// Items are priced by their grist cost, since that's what alchemizing them takes and what they're worth when
+    /// turned back into grist. Chains start and end with items that have one and only go through items that don't,
+    /// since a chain through an item with a grist cost is two shorter ones which are listed if they gain anything.
+    /// Items a recipe uses or makes besides the one carried through have to have a grist cost, the cheapest item
+    /// being used for ingredients matching several
+    pub fn exploits(&self, crafting: &[CraftingRecipe]) -> Vec<Exploit> {
+        let tags = self.resolver().tags();
+        let price = |item: &str| -> Option<PricedItem> {
+            let (cost, cost_location) = self.resolver().item_cost(item)?;
+            Some(PricedItem {
+                item: item.to_string(),
+                count: 1,
+                cost,
+                cost_location,
+            })
+        };
+        let items = |ingredient: &Ingredient| tags.ingredient_items(ingredient).unwrap_or_default();
+        let cheapest = |ingredient: &Ingredient, count: i64| -> Option<PricedItem> {
+            let priced = items(ingredient)
+                .iter()
+                .filter_map(|item| price(item))
+                .min_by_key(|priced| grist_total(&priced.cost))?;
+            Some(PricedItem { count, ..priced })
+        };
+        //Ingredients with how many of each are taken, so an item carried into a recipe is everything it's used for
+        let group = |ingredients: &[&Ingredient]| -> Vec<(Ingredient, i64)> {
+            let mut groups: Vec<(Ingredient, i64)> = vec![];
+            for ingredient in ingredients {
+                match groups.iter_mut().find(|(existing, _)| existing == *ingredient) {
+                    Some((_, count)) => *count += 1,
+                    None => groups.push(((*ingredient).clone(), 1)),
+                }
+            }
+            groups
+        };
+        //Priced items for every group but one, None if any of them can't be priced
+        let others = |groups: &[(Ingredient, i64)], skip: usize| -> Option<Vec<PricedItem>> {
+            groups
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != skip)
+                .map(|(_, (ingredient, count))| cheapest(ingredient, *count))
+                .collect()
+        };
+
+        let mut steps: BTreeMap<String, Vec<ExploitStep>> = BTreeMap::new();
+        for recipe in crafting {
+            let groups = group(&recipe.inputs.iter().collect::<Vec<_>>());
+            for (i, (ingredient, consumed)) in groups.iter().enumerate() {
+                let Some(bought) = others(&groups, i) else {
+                    continue;
+                };
+                for item in items(ingredient) {
+                    steps.entry(item.clone()).or_default().push(ExploitStep {
+                        kind: ExploitKind::Recipe(recipe.kind),
+                        recipe: recipe.location.clone(),
+                        from: item,
+                        consumed: *consumed,
+                        to: recipe.output.clone(),
+                        produced: recipe.count as i64,
+                        bought: bought.clone(),
+                        sold: vec![],
+                        times: 1,
+                    });
+                }
+            }
+        }
+        for edge in self.edges() {
+            let groups = group(&[&edge.recipe.input1, &edge.recipe.input2]);
+            for (i, (ingredient, produced)) in groups.iter().enumerate() {
+                let Some(sold) = others(&groups, i) else {
+                    continue;
+                };
+                for item in items(ingredient) {
+                    steps.entry(edge.output.clone()).or_default().push(ExploitStep {
+                        kind: ExploitKind::Combination,
+                        recipe: edge.location.clone(),
+                        from: edge.output.clone(),
+                        consumed: 1,
+                        to: item,
+                        produced: *produced,
+                        bought: vec![],
+                        sold: sold.clone(),
+                        times: 1,
+                    });
+                }
+            }
+        }
+
+        let mut exploits = vec![];
+        //The same recipes can be gone through carrying different items when every item involved has a grist cost
+        let mut found: BTreeSet<(String, Vec<String>)> = BTreeSet::new();
+        let mut path: Vec<&ExploitStep> = vec![];
+        for item in steps.keys() {
+            let Some(start) = price(item) else {
+                continue;
+            };
+            //Each step tried from the item carried so far, popped once everything after it has been
+            let mut stack: Vec<(usize, usize)> = vec![(0, 0)];
+            let mut carried = vec![item.as_str()];
+            while let Some((depth, next)) = stack.pop() {
+                path.truncate(depth);
+                carried.truncate(depth + 1);
+                let Some(step) = steps.get(carried[depth]).and_then(|from| from.get(next)) else {
+                    continue;
+                };
+                stack.push((depth, next + 1));
+                path.push(step);
+                if let Some(end) = price(&step.to) {
+                    let owned: Vec<ExploitStep> = path.iter().map(|step| (*step).clone()).collect();
+                    let recipes = owned.iter().map(|step| step.recipe.clone()).collect();
+                    if let Some(exploit) = close(&start, &owned, &end) {
+                        if found.insert((item.clone(), recipes)) {
+                            exploits.push(exploit);
+                        }
+                    }
+                } else if depth + 1 < MAX_STEPS && !carried.contains(&step.to.as_str()) {
+                    carried.push(&step.to);
+                    stack.push((depth + 1, 0));
+                }
+            }
+        }
+        exploits
+    }
+}
+
+impl Display for PricedItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} at {} each ({})",
+            self.count,
+            self.item,
+            format_grist(&self.cost),
+            self.cost_location
+        )
+    }
+}
+
+impl Display for ExploitStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (from, to) = (self.consumed * self.times, self.produced * self.times);
+        match self.kind {
+            ExploitKind::Recipe(kind) => {
+                write!(f, "{kind} {from} {} into {to} {} with {}", self.from, self.to, self.recipe)?
+            }
+            ExploitKind::Combination => {
+                write!(f, "alchemize {from} {} instead of {to} {} with {}", self.from, self.to, self.recipe)?
+            }
+        }
+        for item in &self.bought {
+            write!(f, "\n    also alchemizing {}", item.times(self.times))?;
+        }
+        for item in &self.sold {
+            write!(f, "\n    also instead of {}", item.times(self.times))?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Exploit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let recipes: Vec<&str> = self.steps.iter().map(|step| step.recipe.as_str()).collect();
+        writeln!(f, "{} gains {}", recipes.join(" then "), format_grist(&self.gain))?;
+        writeln!(f, "  alchemize {}", self.start)?;
+        for step in &self.steps {
+            writeln!(f, "  {step}")?;
+        }
+        write!(f, "  worth {}", self.end)
+    }
+}
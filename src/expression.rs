@@ -0,0 +1,594 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    str::FromStr,
+};
+
+use crate::{grist_resource, GristTotals, Rounding};
+
+/// A grist cost or amount written in terms of other items' costs, like `9 * minecraft:iron_ingot + build:5`
+///
+/// Supports `+`, `-`, `*`, `/`, brackets and `ceil(...)`, `floor(...)` and `round(...)`. A term whose part after the
+/// last `:` is a number is an amount of grist, with `minestuck:` added if there's no namespace, and other ids are
+/// items. Ids can contain `-`, so subtracting from an item needs spaces around the `-`
+#[derive(Debug, Clone, PartialEq)]
+pub enum GristExpression {
+    Number(f64),
+    /// An amount of one grist type
+    Grist(String, f64),
+    /// The cost of an item
+    Item(String),
+    Negate(Box<GristExpression>),
+    Add(Box<GristExpression>, Box<GristExpression>),
+    Subtract(Box<GristExpression>, Box<GristExpression>),
+    Multiply(Box<GristExpression>, Box<GristExpression>),
+    Divide(Box<GristExpression>, Box<GristExpression>),
+    Round(Rounding, Box<GristExpression>),
+}
+
+/// A grist cost written with expressions, each giving the whole cost, or the amount of one grist type if it has one
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CostFormula {
+    pub terms: Vec<(Option<String>, GristExpression)>,
+}
+
+/// What part of an expression works out to, fractions are only rounded at the end
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Number(f64),
+    Grist(BTreeMap<String, f64>),
+}
+
+impl Value {
+    fn map(self, f: impl Fn(f64) -> f64) -> Value {
+        match self {
+            Value::Number(n) => Value::Number(f(n)),
+            Value::Grist(grist) => Value::Grist(grist.into_iter().map(|(grist, amount)| (grist, f(amount))).collect()),
+        }
+    }
+}
+
+pub(crate) fn is_whole(amount: f64) -> bool {
+    (amount - amount.round()).abs() < 1e-9
+}
+
+/// Grist worked out from expressions as the amounts a grist cost recipe holds, or why it can't hold them
+pub fn recipe_amounts(grist: &GristTotals) -> Result<BTreeMap<String, i32>, String> {
+    grist
+        .iter()
+        .map(|(grist, &amount)| {
+            if amount < 0 {
+                return Err(format!("{amount} {grist} is less than nothing"));
+            }
+            i32::try_from(amount)
+                .map(|amount| (grist.clone(), amount))
+                .map_err(|_| format!("{amount} {grist} is more than a recipe can hold"))
+        })
+        .collect()
+}
+
+impl GristExpression {
+    /// Every item whose cost is used
+    pub fn items(&self) -> BTreeSet<&str> {
+        let mut items = BTreeSet::new();
+        let mut remaining = vec![self];
+        while let Some(expression) = remaining.pop() {
+            match expression {
+                GristExpression::Number(_) | GristExpression::Grist(..) => {}
+                GristExpression::Item(item) => {
+                    items.insert(item.as_str());
+                }
+                GristExpression::Negate(inner) | GristExpression::Round(_, inner) => remaining.push(inner),
+                GristExpression::Add(a, b)
+                | GristExpression::Subtract(a, b)
+                | GristExpression::Multiply(a, b)
+                | GristExpression::Divide(a, b) => remaining.extend([a.as_ref(), b.as_ref()]),
+            }
+        }
+        items
+    }
+
+    /// How many times the item's cost the expression adds, None if it isn't just a multiple of it, such as inside
+    /// `ceil(...)`, so working back from the whole cost to the item's can't be done
+    pub fn coefficient(&self, item: &str) -> Option<f64> {
+        //Numbers multiplying or dividing the item can't use any item costs themselves
+        let number = |expression: &GristExpression| match expression.value(None, &|_| None) {
+            Ok(Value::Number(n)) => Some(n),
+            _ => None,
+        };
+        Some(match self {
+            GristExpression::Item(id) if id == item => 1.0,
+            GristExpression::Number(_) | GristExpression::Grist(..) | GristExpression::Item(_) => 0.0,
+            GristExpression::Negate(inner) => -inner.coefficient(item)?,
+            GristExpression::Add(a, b) => a.coefficient(item)? + b.coefficient(item)?,
+            GristExpression::Subtract(a, b) => a.coefficient(item)? - b.coefficient(item)?,
+            _ if !self.items().contains(item) => 0.0,
+            GristExpression::Multiply(a, b) if !b.items().contains(item) => a.coefficient(item)? * number(b)?,
+            GristExpression::Multiply(a, b) if !a.items().contains(item) => number(a)? * b.coefficient(item)?,
+            GristExpression::Divide(a, b) if !b.items().contains(item) => a.coefficient(item)? / number(b)?,
+            _ => return None,
+        })
+    }
+
+    /// With a grist type, items and grist amounts are just the amount of that type
+    fn value(&self, grist: Option<&str>, cost: &dyn Fn(&str) -> Option<GristTotals>) -> Result<Value, String> {
+        let binary = |a: &GristExpression, b: &GristExpression| Ok::<_, String>((a.value(grist, cost)?, b.value(grist, cost)?));
+        Ok(match self {
+            GristExpression::Number(n) => Value::Number(*n),
+            GristExpression::Grist(id, amount) => match grist {
+                Some(grist) => Value::Number(if id == grist { *amount } else { 0.0 }),
+                None => Value::Grist(BTreeMap::from([(id.clone(), *amount)])),
+            },
+            GristExpression::Item(item) => {
+                let item_cost = cost(item).ok_or_else(|| format!("{item} has no cost"))?;
+                match grist {
+                    Some(grist) => Value::Number(item_cost.get(grist).copied().unwrap_or_default() as f64),
+                    None => Value::Grist(item_cost.into_iter().map(|(grist, amount)| (grist, amount as f64)).collect()),
+                }
+            }
+            GristExpression::Negate(inner) => inner.value(grist, cost)?.map(|n| -n),
+            GristExpression::Add(a, b) | GristExpression::Subtract(a, b) => {
+                let sign = if matches!(self, GristExpression::Add(..)) { 1.0 } else { -1.0 };
+                match binary(a, b)? {
+                    (Value::Number(a), Value::Number(b)) => Value::Number(a + sign * b),
+                    (Value::Grist(mut a), Value::Grist(b)) => {
+                        for (grist, amount) in b {
+                            *a.entry(grist).or_default() += sign * amount;
+                        }
+                        Value::Grist(a)
+                    }
+                    _ => return Err("Can't add a number and a grist cost, write amounts of grist like build:5".into()),
+                }
+            }
+            GristExpression::Multiply(a, b) => match binary(a, b)? {
+                (Value::Number(a), Value::Number(b)) => Value::Number(a * b),
+                (Value::Number(n), value) | (value, Value::Number(n)) => value.map(|amount| amount * n),
+                _ => return Err("Can't multiply two grist costs".into()),
+            },
+            GristExpression::Divide(a, b) => match binary(a, b)? {
+                (_, Value::Number(0.0)) => return Err("Division by zero".into()),
+                (value, Value::Number(n)) => value.map(|amount| amount / n),
+                _ => return Err("Can't divide by a grist cost".into()),
+            },
+            GristExpression::Round(rounding, inner) => inner.value(grist, cost)?.map(|amount| match rounding {
+                Rounding::Up => amount.ceil(),
+                Rounding::Down => amount.floor(),
+                Rounding::Nearest => amount.round(),
+            }),
+        })
+    }
+
+    /// Works out the cost, or with a grist type the amount of just that type, which has to come to whole amounts
+    pub fn evaluate(&self, grist: Option<&str>, cost: &dyn Fn(&str) -> Option<GristTotals>) -> Result<GristTotals, String> {
+        let grist = grist.map(grist_resource);
+        let amounts = match (self.value(grist.as_deref(), cost)?, grist) {
+            (Value::Number(n), Some(grist)) => BTreeMap::from([(grist, n)]),
+            (Value::Number(_), None) => {
+                return Err("Works out to a number rather than grist, write amounts of grist like build:5".into())
+            }
+            //Types that cancel out are left out of whole costs
+            (Value::Grist(amounts), _) => amounts.into_iter().filter(|(_, amount)| *amount != 0.0).collect(),
+        };
+        amounts
+            .into_iter()
+            .map(|(grist, amount)| {
+                if is_whole(amount) {
+                    Ok((grist, amount.round() as i64))
+                } else {
+                    Err(format!("{amount} {grist} isn't a whole number, use ceil, floor or round"))
+                }
+            })
+            .collect()
+    }
+}
+
+impl CostFormula {
+    /// Every item whose cost is used
+    pub fn items(&self) -> BTreeSet<&str> {
+        self.terms.iter().flat_map(|(_, expression)| expression.items()).collect()
+    }
+
+    /// Adds up every term
+    pub fn evaluate(&self, cost: &dyn Fn(&str) -> Option<GristTotals>) -> Result<GristTotals, String> {
+        let mut total = GristTotals::new();
+        for (grist, expression) in &self.terms {
+            for (grist, amount) in expression.evaluate(grist.as_deref(), cost)? {
+                *total.entry(grist).or_default() += amount;
+            }
+        }
+        Ok(total)
+    }
+}
+
+/// Works out the cost of every formula, using the results of ones for items other formulas use first
+///
+/// Items without a formula use their existing cost. When several formulas are for the same item the last one is
+/// used by the others. Formulas that use each other get a circular reference error
+pub fn evaluate_formulas(
+    formulas: &[(String, CostFormula)],
+    existing: impl Fn(&str) -> Option<GristTotals>,
+) -> Vec<Result<GristTotals, String>> {
+    let mut evaluator = FormulaEvaluator {
+        formulas,
+        by_item: formulas
+            .iter()
+            .enumerate()
+            .map(|(i, (item, _))| (item.as_str(), i))
+            .collect(),
+        existing: &existing,
+        results: vec![None; formulas.len()],
+        stack: vec![],
+    };
+    for i in 0..formulas.len() {
+        evaluator.visit(i);
+    }
+    evaluator.results.into_iter().map(Option::unwrap).collect()
+}
+
+struct FormulaEvaluator<'a> {
+    formulas: &'a [(String, CostFormula)],
+    by_item: BTreeMap<&'a str, usize>,
+    existing: &'a dyn Fn(&str) -> Option<GristTotals>,
+    results: Vec<Option<Result<GristTotals, String>>>,
+    /// Formulas being worked out, each used by the one before it
+    stack: Vec<usize>,
+}
+
+impl FormulaEvaluator<'_> {
+    fn visit(&mut self, i: usize) {
+        if self.results[i].is_some() {
+            return;
+        }
+        if let Some(start) = self.stack.iter().position(|j| *j == i) {
+            let cycle: Vec<&str> = self.stack[start..]
+                .iter()
+                .chain([&i])
+                .map(|j| self.formulas[*j].0.as_str())
+                .collect();
+            let error = format!("Circular reference {}", cycle.join(" -> "));
+            for j in &self.stack[start..] {
+                self.results[*j] = Some(Err(error.clone()));
+            }
+            return;
+        }
+        let (_, formula) = &self.formulas[i];
+        self.stack.push(i);
+        for item in formula.items() {
+            if let Some(j) = self.by_item.get(item) {
+                self.visit(*j);
+            }
+        }
+        self.stack.pop();
+        //Already set if this is part of a cycle
+        if self.results[i].is_some() {
+            return;
+        }
+        let failed = formula.items().into_iter().find(|item| {
+            self.by_item
+                .get(item)
+                .is_some_and(|j| matches!(self.results[*j], Some(Err(_))))
+        });
+        let result = match failed {
+            Some(item) => Err(format!("Uses {item}, whose cost couldn't be worked out")),
+            None => formula.evaluate(&|item| match self.by_item.get(item) {
+                Some(j) => self.results[*j].clone().and_then(Result::ok),
+                None => (self.existing)(item),
+            }),
+        };
+        self.results[i] = Some(result);
+    }
+}
+
+impl GristExpression {
+    /// How tightly it binds, so brackets are only written where they're needed
+    fn precedence(&self) -> u8 {
+        match self {
+            GristExpression::Add(..) | GristExpression::Subtract(..) => 0,
+            GristExpression::Multiply(..) | GristExpression::Divide(..) => 1,
+            _ => 2,
+        }
+    }
+
+    fn fmt_inside(&self, f: &mut std::fmt::Formatter<'_>, precedence: u8) -> std::fmt::Result {
+        if self.precedence() < precedence {
+            write!(f, "({self})")
+        } else {
+            write!(f, "{self}")
+        }
+    }
+}
+
+impl Display for GristExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        //The right side of - and / also needs brackets at the same precedence, since they don't group that way
+        let (left, symbol, right) = match self {
+            GristExpression::Number(n) => return write!(f, "{n}"),
+            GristExpression::Grist(grist, amount) => {
+                return write!(f, "{}:{amount}", grist.strip_prefix("minestuck:").unwrap_or(grist))
+            }
+            GristExpression::Item(item) => return write!(f, "{item}"),
+            GristExpression::Negate(inner) => {
+                write!(f, "-")?;
+                return inner.fmt_inside(f, 2);
+            }
+            GristExpression::Round(rounding, inner) => {
+                let function = match rounding {
+                    Rounding::Up => "ceil",
+                    Rounding::Down => "floor",
+                    Rounding::Nearest => "round",
+                };
+                return write!(f, "{function}({inner})");
+            }
+            GristExpression::Add(a, b) => (a, "+", b),
+            GristExpression::Subtract(a, b) => (a, "-", b),
+            GristExpression::Multiply(a, b) => (a, "*", b),
+            GristExpression::Divide(a, b) => (a, "/", b),
+        };
+        let precedence = self.precedence();
+        left.fmt_inside(f, precedence)?;
+        write!(f, " {symbol} ")?;
+        let right_precedence = if matches!(self, GristExpression::Subtract(..) | GristExpression::Divide(..)) {
+            precedence + 1
+        } else {
+            precedence
+        };
+        right.fmt_inside(f, right_precedence)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Id(String),
+    Symbol(char),
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '+' | '-' | '*' | '/' | '(' | ')' => tokens.push(Token::Symbol(c)),
+            '×' => tokens.push(Token::Symbol('*')),
+            c if c.is_ascii_alphanumeric() || "_.:#".contains(c) => {
+                let mut id = c.to_string();
+                //A - after the namespace is part of the id
+                while let Some(next) = chars.next_if(|next| {
+                    next.is_ascii_alphanumeric() || "_.:/#".contains(*next) || (*next == '-' && id.contains(':'))
+                }) {
+                    id.push(next);
+                }
+                //Checked so words like inf aren't numbers
+                let number = id.starts_with(|c: char| c.is_ascii_digit() || c == '.');
+                match id.parse() {
+                    Ok(n) if number => tokens.push(Token::Number(n)),
+                    _ => tokens.push(Token::Id(id)),
+                }
+            }
+            c => return Err(format!("Unexpected \"{c}\" in \"{s}\"")),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over the tokens, `*` and `/` binding tighter than `+` and `-`
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), String> {
+        match self.next() {
+            Some(Token::Symbol(c)) if c == symbol => Ok(()),
+            _ => Err(format!("Expected \"{symbol}\"")),
+        }
+    }
+
+    fn sum(&mut self) -> Result<GristExpression, String> {
+        let mut expression = self.product()?;
+        while let Some(Token::Symbol(c @ ('+' | '-'))) = self.peek().cloned() {
+            self.position += 1;
+            let right = Box::new(self.product()?);
+            expression = if c == '+' {
+                GristExpression::Add(Box::new(expression), right)
+            } else {
+                GristExpression::Subtract(Box::new(expression), right)
+            };
+        }
+        Ok(expression)
+    }
+
+    fn product(&mut self) -> Result<GristExpression, String> {
+        let mut expression = self.term()?;
+        while let Some(Token::Symbol(c @ ('*' | '/'))) = self.peek().cloned() {
+            self.position += 1;
+            let right = Box::new(self.term()?);
+            expression = if c == '*' {
+                GristExpression::Multiply(Box::new(expression), right)
+            } else {
+                GristExpression::Divide(Box::new(expression), right)
+            };
+        }
+        Ok(expression)
+    }
+
+    fn term(&mut self) -> Result<GristExpression, String> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(GristExpression::Number(n)),
+            Some(Token::Symbol('-')) => Ok(GristExpression::Negate(Box::new(self.term()?))),
+            Some(Token::Symbol('(')) => {
+                let expression = self.sum()?;
+                self.expect(')')?;
+                Ok(expression)
+            }
+            Some(Token::Id(id)) if self.peek() == Some(&Token::Symbol('(')) => {
+                let rounding = match id.as_str() {
+                    "ceil" => Rounding::Up,
+                    "floor" => Rounding::Down,
+                    "round" => Rounding::Nearest,
+                    _ => return Err(format!("Unknown function \"{id}\", expected ceil, floor or round")),
+                };
+                self.position += 1;
+                let expression = self.sum()?;
+                self.expect(')')?;
+                Ok(GristExpression::Round(rounding, Box::new(expression)))
+            }
+            Some(Token::Id(id)) => match id.rsplit_once(':').map(|(grist, amount)| (grist, amount.parse::<f64>())) {
+                Some((grist, Ok(amount))) => Ok(GristExpression::Grist(grist_resource(grist), amount)),
+                _ => Ok(GristExpression::Item(id)),
+            },
+            Some(Token::Symbol(c)) => Err(format!("Unexpected \"{c}\"")),
+            None => Err("Expression ends too early".into()),
+        }
+    }
+}
+
+impl FromStr for GristExpression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            position: 0,
+        };
+        let expression = parser.sum().map_err(|e| format!("{e} in \"{s}\""))?;
+        if parser.position < parser.tokens.len() {
+            let token = match &parser.tokens[parser.position] {
+                Token::Number(n) => n.to_string(),
+                Token::Id(id) => id.clone(),
+                Token::Symbol(c) => c.to_string(),
+            };
+            return Err(format!("Unexpected \"{token}\" in \"{s}\""));
+        }
+        Ok(expression)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn costs(item: &str) -> Option<GristTotals> {
+        match item {
+            "a:ingot" => Some(BTreeMap::from([("minestuck:build".to_string(), 2), ("minestuck:rust".to_string(), 6)])),
+            "a:my-item" => Some(BTreeMap::from([("minestuck:build".to_string(), 10)])),
+            _ => None,
+        }
+    }
+
+    fn build(amount: i64) -> GristTotals {
+        BTreeMap::from([("minestuck:build".to_string(), amount)])
+    }
+
+    fn evaluate(expression: &str, grist: Option<&str>) -> Result<GristTotals, String> {
+        expression.parse::<GristExpression>()?.evaluate(grist, &costs)
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(evaluate("build:1 + 2 * 3 * build:1", None), Ok(build(7)));
+        assert!(evaluate("(build:1 + 2) * 3", None).is_err());
+        assert_eq!(evaluate("(build:1 + build:2) * 3", None), Ok(build(9)));
+        assert_eq!(evaluate("build:12 / 2 / 3", None), Ok(build(2)));
+        assert_eq!(evaluate("build:10 - build:4 - build:3", None), Ok(build(3)));
+        assert_eq!(evaluate("-build:2 + build:5", None), Ok(build(3)));
+    }
+
+    #[test]
+    fn minus_in_ids() {
+        assert_eq!(evaluate("a:my-item", None), Ok(build(10)));
+        assert_eq!(evaluate("a:my-item - build:3", None), Ok(build(7)));
+        assert_eq!(evaluate("a:my-item -build:3", None), Ok(build(7)));
+        //Without a space the - is part of the id
+        let expression: GristExpression = "a:my-item-build:3".parse().unwrap();
+        assert_eq!(expression, GristExpression::Grist("a:my-item-build".to_string(), 3.0));
+    }
+
+    #[test]
+    fn grist_of_one_type() {
+        let rust = BTreeMap::from([("minestuck:rust".to_string(), 54)]);
+        assert_eq!(evaluate("9 * a:ingot", Some("rust")), Ok(rust));
+        assert!(evaluate("a:ingot / 4", Some("build")).is_err());
+        assert_eq!(evaluate("ceil(a:ingot / 4)", Some("build")), Ok(build(1)));
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let texts = ["build:10 - (build:4 - build:3)", "a:my-item - build:3", "ceil(a:ingot / (2 * 3))", "-(build:1 + 2)"];
+        for text in texts {
+            let expression: GristExpression = text.parse().unwrap();
+            assert_eq!(expression.to_string(), text);
+            assert_eq!(expression.to_string().parse::<GristExpression>(), Ok(expression));
+        }
+    }
+
+    #[test]
+    fn coefficient() {
+        let expression: GristExpression = "2 * (a:x + a:y) / 4 - a:x + build:1".parse().unwrap();
+        assert_eq!(expression.coefficient("a:x"), Some(-0.5));
+        assert_eq!(expression.coefficient("a:y"), Some(0.5));
+        assert_eq!(expression.coefficient("a:z"), Some(0.0));
+        let rounded: GristExpression = "ceil(a:x / 2)".parse().unwrap();
+        assert_eq!(rounded.coefficient("a:x"), None);
+    }
+
+    fn formula(expression: &str) -> CostFormula {
+        CostFormula {
+            terms: vec![(None, expression.parse().unwrap())],
+        }
+    }
+
+    #[test]
+    fn formulas_in_order_of_use() {
+        let formulas = vec![
+            ("a:block".to_string(), formula("9 * a:nugget_block")),
+            ("a:nugget_block".to_string(), formula("a:ingot")),
+        ];
+        let results = evaluate_formulas(&formulas, costs);
+        let block = BTreeMap::from([("minestuck:build".to_string(), 18), ("minestuck:rust".to_string(), 54)]);
+        assert_eq!(results[0], Ok(block));
+        assert_eq!(results[1], Ok(costs("a:ingot").unwrap()));
+    }
+
+    #[test]
+    fn circular_formulas() {
+        let formulas = vec![
+            ("a:x".to_string(), formula("a:y + build:1")),
+            ("a:y".to_string(), formula("a:x")),
+            ("a:z".to_string(), formula("a:y")),
+            ("a:self".to_string(), formula("a:self + build:1")),
+        ];
+        let results = evaluate_formulas(&formulas, costs);
+        assert_eq!(results[0], Err("Circular reference a:x -> a:y -> a:x".to_string()));
+        assert_eq!(results[1], Err("Circular reference a:x -> a:y -> a:x".to_string()));
+        assert_eq!(results[2], Err("Uses a:y, whose cost couldn't be worked out".to_string()));
+        assert_eq!(results[3], Err("Circular reference a:self -> a:self".to_string()));
+    }
+
+    #[test]
+    fn amounts_a_recipe_holds() {
+        let grist = |amount| BTreeMap::from([("minestuck:build".to_string(), amount)]);
+        assert_eq!(recipe_amounts(&grist(5)), Ok(BTreeMap::from([("minestuck:build".to_string(), 5)])));
+        assert_eq!(recipe_amounts(&grist(-48)), Err("-48 minestuck:build is less than nothing".to_string()));
+        assert_eq!(
+            recipe_amounts(&grist(-5_000_000_000)),
+            Err("-5000000000 minestuck:build is less than nothing".to_string())
+        );
+        assert_eq!(
+            recipe_amounts(&grist(5_000_000_000)),
+            Err("5000000000 minestuck:build is more than a recipe can hold".to_string())
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{recipe_id, CombinationMode, CombinationRecipe, CostResolver, Datapack, Ingredient, Recipe, ResultItem};
+
+/// Combination recipes from every pack loaded as a graph from inputs to outputs, with the grist costs needed to
+/// know which outputs can be alchemized
+#[derive(Debug, Clone, Default)]
+pub struct AlchemyGraph {
+    resolver: CostResolver,
+    /// By location, which packs loaded later replace
+    edges: BTreeMap<String, AlchemyEdge>,
+    /// Locations of the combinations making each item
+    by_output: BTreeMap<String, BTreeSet<String>>,
+    /// Locations of the combinations using each node or item matched by an input
+    by_input: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// A combination recipe, with the inputs as graph nodes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlchemyEdge {
+    /// Name of the pack the recipe comes from
+    pub pack: String,
+    pub location: String,
+    pub recipe: CombinationRecipe,
+    /// Item ids, or the csv form of anything matching several items such as `#minecraft:logs`
+    pub inputs: [String; 2],
+    pub output: String,
+    /// Items each input matches, None if a tag in it can't be resolved
+    input_items: [Option<BTreeSet<String>>; 2],
+}
+
+impl AlchemyEdge {
+    pub fn mode(&self) -> CombinationMode {
+        self.recipe.mode
+    }
+
+    /// Items each input matches, None if a tag in it can't be resolved
+    pub fn input_items(&self) -> [Option<&BTreeSet<String>>; 2] {
+        [self.input_items[0].as_ref(), self.input_items[1].as_ref()]
+    }
+
+    /// Keys the combination is found under in the input index
+    fn input_keys(&self) -> BTreeSet<String> {
+        let mut keys: BTreeSet<String> = self.inputs.iter().cloned().collect();
+        keys.extend(self.input_items.iter().flatten().flatten().cloned());
+        keys
+    }
+}
+
+/// The graph node for an ingredient, its item id if it's a single item
+pub fn node_name(ingredient: &Ingredient) -> String {
+    match ingredient {
+        Ingredient::Item(id) => id.clone(),
+        _ => ingredient.to_string(),
+    }
+}
+
+impl AlchemyGraph {
+    /// Grist costs and tags come from the resolver, which should have the same packs added to it
+    pub fn new(resolver: CostResolver) -> AlchemyGraph {
+        AlchemyGraph {
+            resolver,
+            ..Default::default()
+        }
+    }
+
+    /// Adds the combinations in a pack on top of the ones added before, replacing recipes at the same location and
+    /// removing ones its filter hides
+    pub fn add_pack(&mut self, name: &str, datapack: &Datapack) {
+        let hidden: Vec<String> = self
+            .edges
+            .keys()
+            .filter(|location| recipe_id(location).is_some_and(|id| datapack.mcmeta.hides_recipe(&id)))
+            .cloned()
+            .collect();
+        for location in hidden {
+            self.remove(&location);
+        }
+        for (location, recipe) in &datapack.recipes {
+            if let Recipe::Combination(recipe) = recipe {
+                self.add(name, location, recipe);
+            }
+        }
+    }
+
+    /// Adds a single combination, replacing any at the same location
+    pub fn add(&mut self, pack: &str, location: &str, recipe: &CombinationRecipe) {
+        self.remove(location);
+        let ResultItem::Item(output) = &recipe.output;
+        let tags = self.resolver.tags();
+        let edge = AlchemyEdge {
+            pack: pack.to_string(),
+            location: location.to_string(),
+            recipe: recipe.clone(),
+            inputs: [node_name(&recipe.input1), node_name(&recipe.input2)],
+            output: output.clone(),
+            input_items: [
+                tags.ingredient_items(&recipe.input1).ok(),
+                tags.ingredient_items(&recipe.input2).ok(),
+            ],
+        };
+        self.by_output
+            .entry(edge.output.clone())
+            .or_default()
+            .insert(location.to_string());
+        for key in edge.input_keys() {
+            self.by_input.entry(key).or_default().insert(location.to_string());
+        }
+        self.edges.insert(location.to_string(), edge);
+    }
+
+    /// Removes the combination at a location, returning it if there was one
+    pub fn remove(&mut self, location: &str) -> Option<AlchemyEdge> {
+        let edge = self.edges.remove(location)?;
+        if let Some(locations) = self.by_output.get_mut(&edge.output) {
+            locations.remove(location);
+        }
+        for key in edge.input_keys() {
+            if let Some(locations) = self.by_input.get_mut(&key) {
+                locations.remove(location);
+            }
+        }
+        Some(edge)
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = &AlchemyEdge> {
+        self.edges.values()
+    }
+
+    pub fn edge(&self, location: &str) -> Option<&AlchemyEdge> {
+        self.edges.get(location)
+    }
+
+    /// Every input node and output item
+    pub fn nodes(&self) -> BTreeSet<&str> {
+        self.edges
+            .values()
+            .flat_map(|edge| [edge.inputs[0].as_str(), edge.inputs[1].as_str(), edge.output.as_str()])
+            .collect()
+    }
+
+    /// Combinations that make an item
+    pub fn recipes_for(&self, item: &str) -> Vec<&AlchemyEdge> {
+        self.lookup(&self.by_output, item)
+    }
+
+    /// Combinations that take an item as either input, including through a tag, or a node such as `#minecraft:logs`
+    pub fn uses_of(&self, node: &str) -> Vec<&AlchemyEdge> {
+        self.lookup(&self.by_input, node)
+    }
+
+    fn lookup(&self, index: &BTreeMap<String, BTreeSet<String>>, key: &str) -> Vec<&AlchemyEdge> {
+        index
+            .get(key)
+            .into_iter()
+            .flatten()
+            .filter_map(|location| self.edges.get(location))
+            .collect()
+    }
+
+    pub fn is_output(&self, item: &str) -> bool {
+        self.by_output.get(item).is_some_and(|locations| !locations.is_empty())
+    }
+
+    /// Whether the item has a grist cost, so it can be alchemized once its card is made
+    pub fn has_grist_cost(&self, item: &str) -> bool {
+        self.resolver
+            .resolve(&Ingredient::Item(item.to_string()))
+            .effective()
+            .is_some()
+    }
+
+    pub fn resolver(&self) -> &CostResolver {
+        &self.resolver
+    }
+
+    /// Outputs of combinations without a grist cost, which can't be alchemized
+    pub fn without_grist_cost(&self) -> Vec<&str> {
+        self.by_output
+            .iter()
+            .filter(|(_, locations)| !locations.is_empty())
+            .map(|(item, _)| item.as_str())
+            .filter(|item| !self.has_grist_cost(item))
+            .collect()
+    }
+
+    /// Items that can be had, assuming every item that isn't the output of a combination can be found in the world
+    ///
+    /// Outputs can be had if they have a grist cost and a combination making them has inputs that can be had.
+    /// Inputs with tags that can't be resolved are assumed to be obtainable
+    pub fn obtainable(&self) -> BTreeSet<&str> {
+        let mut obtainable: BTreeSet<&str> = self
+            .edges
+            .values()
+            .flat_map(|edge| edge.input_items.iter().flatten().flatten())
+            .map(String::as_str)
+            .filter(|item| !self.is_output(item))
+            .collect();
+        let alchemizable: BTreeSet<&str> = self
+            .by_output
+            .keys()
+            .map(String::as_str)
+            .filter(|item| self.is_output(item) && self.has_grist_cost(item))
+            .collect();
+        //Each item made possible can make more possible, so go until nothing changes
+        let mut pending: Vec<&AlchemyEdge> = self
+            .edges
+            .values()
+            .filter(|edge| alchemizable.contains(edge.output.as_str()))
+            .collect();
+        loop {
+            let count = pending.len();
+            pending.retain(|edge| {
+                if obtainable.contains(edge.output.as_str()) {
+                    return false;
+                }
+                let inputs_obtainable = edge.input_items.iter().all(|items| match items {
+                    Some(items) => items.iter().any(|item| obtainable.contains(item.as_str())),
+                    None => true,
+                });
+                if inputs_obtainable {
+                    obtainable.insert(&edge.output);
+                }
+                !inputs_obtainable
+            });
+            if pending.len() == count {
+                break;
+            }
+        }
+        obtainable
+    }
+
+    /// Outputs with a grist cost that still can't be made, because every combination for them needs an input that
+    /// can't be had
+    pub fn unobtainable(&self) -> Vec<&str> {
+        let obtainable = self.obtainable();
+        self.by_output
+            .iter()
+            .filter(|(_, locations)| !locations.is_empty())
+            .map(|(item, _)| item.as_str())
+            .filter(|item| !obtainable.contains(item) && self.has_grist_cost(item))
+            .collect()
+    }
+
+    /// Groups of items that are each needed, through some chain of combinations, to make the others
+    ///
+    /// Each cycle is listed from its first item in order, an item combined with itself to make itself is a cycle
+    /// of one
+    pub fn cycles(&self) -> Vec<Vec<&str>> {
+        //Edges from each item an input matches to the output
+        let mut next: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for edge in self.edges.values() {
+            for item in edge.input_items.iter().flatten().flatten() {
+                next.entry(item).or_default().insert(&edge.output);
+            }
+        }
+        strongly_connected(&next)
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1 || next.get(component[0]).is_some_and(|outputs| outputs.contains(component[0]))
+            })
+            .collect()
+    }
+}
+
+/// Tarjan's algorithm without recursion, since combination chains can be long, giving each component sorted
+fn strongly_connected<'a>(next: &BTreeMap<&'a str, BTreeSet<&'a str>>) -> Vec<Vec<&'a str>> {
+    let mut index: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut low: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut stack: Vec<&str> = vec![];
+    let mut on_stack: BTreeSet<&str> = BTreeSet::new();
+    let mut components = vec![];
+    //Each frame is a node being visited and the successors it has left
+    let mut frames: Vec<(&str, Vec<&str>)> = vec![];
+    for &start in next.keys() {
+        if index.contains_key(start) {
+            continue;
+        }
+        let mut to_visit = Some(start);
+        loop {
+            if let Some(node) = to_visit.take() {
+                index.insert(node, index.len());
+                low.insert(node, index[node]);
+                stack.push(node);
+                on_stack.insert(node);
+                let successors = next.get(node).map(|s| s.iter().rev().copied().collect()).unwrap_or_default();
+                frames.push((node, successors));
+            }
+            let Some((node, successors)) = frames.last_mut() else {
+                break;
+            };
+            let node = *node;
+            if let Some(successor) = successors.pop() {
+                if !index.contains_key(successor) {
+                    to_visit = Some(successor);
+                } else if on_stack.contains(successor) {
+                    low.insert(node, low[node].min(index[successor]));
+                }
+                continue;
+            }
+            frames.pop();
+            if let Some((parent, _)) = frames.last() {
+                low.insert(parent, low[parent].min(low[node]));
+            }
+            if low[node] == index[node] {
+                let mut component = vec![];
+                while let Some(member) = stack.pop() {
+                    on_stack.remove(member);
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                component.sort();
+                components.push(component);
+            }
+        }
+    }
+    components
+}
@@ -0,0 +1,162 @@
+use std::{collections::BTreeSet, fmt::Display};
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{did_you_mean, grist_resource, validate_resource_location, Config, Datapack, GristCostRecipe};
+
+/// Where a pack's grist types are kept, next to pack.mcmeta. Minestuck and its addons register grist types in code,
+/// so this is only read by this project's tools and is kept out of the data Minecraft loads
+pub const GRIST_TYPE_FILE: &str = "grist_types.json";
+
+/// A grist type added by an addon, kept in [`GRIST_TYPE_FILE`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GristType {
+    /// Written as `#rrggbb`
+    #[serde(serialize_with = "serialize_color", deserialize_with = "deserialize_color")]
+    pub color: u32,
+    /// How much the grist is worth compared to build grist
+    pub value: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub candy_item: Option<String>,
+    /// Secondary grist isn't given out as a primary type for a player's land or underlings
+    #[serde(default)]
+    pub secondary: bool,
+    /// Whether underlings can be made of this grist
+    #[serde(default)]
+    pub underling: bool,
+}
+
+impl Default for GristType {
+    fn default() -> Self {
+        Self {
+            color: 0xffffff,
+            value: 1.0,
+            candy_item: None,
+            secondary: false,
+            underling: false,
+        }
+    }
+}
+
+impl GristType {
+    pub fn is_valid(&self) -> bool {
+        self.color <= 0xffffff
+            && self.value > 0.0
+            && self.candy_item.as_deref().is_none_or(validate_resource_location)
+    }
+}
+
+fn serialize_color<S>(color: &u32, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    format!("#{color:06x}").serialize(serializer)
+}
+
+fn deserialize_color<'de, D>(deserializer: D) -> Result<u32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let color = String::deserialize(deserializer)?;
+    parse_color(&color).ok_or_else(|| D::Error::custom(format!("Invalid color {color}, expected #rrggbb")))
+}
+
+/// Reads a color written as `#rrggbb` or `rrggbb`
+pub fn parse_color(color: &str) -> Option<u32> {
+    let hex = color.strip_prefix('#').unwrap_or(color);
+    if hex.len() == 6 {
+        u32::from_str_radix(hex, 16).ok()
+    } else {
+        None
+    }
+}
+
+/// Grist types built into Minestuck
+pub const MINESTUCK_GRIST: [&str; 21] = [
+    "minestuck:build",
+    "minestuck:amber",
+    "minestuck:caulk",
+    "minestuck:chalk",
+    "minestuck:iodine",
+    "minestuck:shale",
+    "minestuck:tar",
+    "minestuck:cobalt",
+    "minestuck:marble",
+    "minestuck:mercury",
+    "minestuck:quartz",
+    "minestuck:sulfur",
+    "minestuck:amethyst",
+    "minestuck:garnet",
+    "minestuck:ruby",
+    "minestuck:rust",
+    "minestuck:diamond",
+    "minestuck:gold",
+    "minestuck:uranium",
+    "minestuck:artifact",
+    "minestuck:zillium",
+];
+
+/// Every grist type that can be used, to catch typos that are still valid ids
+#[derive(Debug, Clone, Default)]
+pub struct GristRegistry {
+    known: BTreeSet<String>,
+}
+
+/// A grist type in a recipe that isn't in the [`GristRegistry`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownGrist {
+    pub location: String,
+    pub grist: String,
+    pub suggestion: Option<String>,
+}
+
+impl GristRegistry {
+    /// Minestuck's grist types and the extra ones in the config
+    pub fn new(config: &Config) -> GristRegistry {
+        let mut known: BTreeSet<String> = MINESTUCK_GRIST.iter().map(|grist| grist.to_string()).collect();
+        known.extend(config.extra_grist.iter().map(|grist| grist_resource(grist)));
+        GristRegistry { known }
+    }
+
+    /// Adds the grist types a datapack defines
+    pub fn add_datapack(&mut self, datapack: &Datapack) {
+        self.known.extend(datapack.grist_types.keys().cloned());
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.known.contains(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.known.iter().map(String::as_str)
+    }
+
+    /// The known grist type closest to an unknown one, if any are close enough to be a typo
+    pub fn suggest(&self, id: &str) -> Option<&str> {
+        did_you_mean(id, self.iter())
+    }
+
+    /// Unknown grist types in a grist cost recipe at location
+    pub fn check(&self, location: &str, recipe: &GristCostRecipe) -> Vec<UnknownGrist> {
+        recipe
+            .grist_cost
+            .keys()
+            .filter(|grist| !self.contains(grist))
+            .map(|grist| UnknownGrist {
+                location: location.to_string(),
+                grist: grist.clone(),
+                suggestion: self.suggest(grist).map(str::to_string),
+            })
+            .collect()
+    }
+}
+
+impl Display for UnknownGrist {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unknown grist type {} in {}", self.grist, self.location)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, ", did you mean {suggestion}?")?;
+        }
+        Ok(())
+    }
+}
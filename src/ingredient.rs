@@ -0,0 +1,469 @@
+use std::{cmp::Ordering, fmt::Display};
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Value};
+
+use crate::{split_outside_brackets, stable_hash, validate_resource_location};
+
+/// Mod loader whose json format is used for anything vanilla doesn't define
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Loader {
+    #[default]
+    Forge,
+    NeoForge,
+}
+
+impl Display for Loader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Loader::Forge => write!(f, "forge"),
+            Loader::NeoForge => write!(f, "neoforge"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Ingredient {
+    Item(String),
+    Tag(String),
+    /// Matches if any of these match, written as a json array
+    Alternatives(Vec<Ingredient>),
+    Nbt(NbtIngredient),
+}
+
+/// An ingredient that also checks the NBT or data components of the item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NbtIngredient {
+    pub loader: Loader,
+    pub format: NbtFormat,
+    pub items: Vec<String>,
+    /// SNBT as a json string or NBT as a json object, or the data components for [`NbtFormat::Components`]
+    pub data: Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NbtFormat {
+    /// `forge:nbt` or `neoforge:nbt`, the item's NBT has to be exactly the same
+    Nbt,
+    /// `forge:partial_nbt` or `neoforge:partial_nbt`, the item's NBT has to contain everything given
+    PartialNbt,
+    /// `neoforge:components` from 1.20.5 on, strict requires the components to be exactly the same
+    Components { strict: bool },
+}
+
+impl Ingredient {
+    /// Parses the csv form, `modid:item`, `#modid:tag`, alternatives separated by `|`, and items with NBT or
+    /// components in braces after them, `modid:item{...}`, or `modid:item~{...}` to only require what is given
+    ///
+    /// Alternatives that are all items with the same partial NBT or components are one ingredient for several items
+    pub fn parse(s: &str, loader: Loader) -> Result<Ingredient, String> {
+        let alternatives = split_outside_brackets(s, '|');
+        if alternatives.len() == 1 {
+            return Ingredient::parse_single(s, loader);
+        }
+        let alternatives: Vec<Ingredient> = alternatives
+            .into_iter()
+            .map(|alternative| Ingredient::parse_single(alternative, loader))
+            .collect::<Result<_, _>>()?;
+        let nbt: Vec<&NbtIngredient> = alternatives
+            .iter()
+            .filter_map(|alternative| match alternative {
+                Ingredient::Nbt(nbt) => Some(nbt),
+                _ => None,
+            })
+            .collect();
+        //Exact NBT is only for one item
+        let shared = nbt.len() == alternatives.len()
+            && nbt[0].format != NbtFormat::Nbt
+            && nbt.iter().all(|other| (other.format, &other.data) == (nbt[0].format, &nbt[0].data));
+        if shared {
+            Ok(Ingredient::Nbt(NbtIngredient {
+                items: nbt.iter().flat_map(|nbt| nbt.items.iter().cloned()).collect(),
+                ..nbt[0].clone()
+            }))
+        } else {
+            Ok(Ingredient::Alternatives(alternatives))
+        }
+    }
+
+    fn parse_single(s: &str, loader: Loader) -> Result<Ingredient, String> {
+        let s = s.trim();
+        if let Some(tag) = s.strip_prefix('#') {
+            return Ok(Ingredient::Tag(tag.to_string()));
+        }
+        let Some(start) = s.find('{') else {
+            return Ok(Ingredient::Item(s.to_string()));
+        };
+        let (item, data) = s.split_at(start);
+        let (item, partial) = match item.strip_suffix('~') {
+            Some(item) => (item, true),
+            None => (item, false),
+        };
+        //NBT that's json is kept as json, so an ingredient read from json is written back the same
+        let json = serde_json::from_str::<Value>(data).ok().filter(Value::is_object);
+        //Data components are always namespaced, NBT tags usually aren't
+        let components = loader == Loader::NeoForge
+            && json
+                .as_ref()
+                .and_then(Value::as_object)
+                .is_some_and(|object| object.keys().all(|key| key.contains(':')));
+        let format = match (components, partial) {
+            (true, _) => NbtFormat::Components { strict: !partial },
+            (false, false) => NbtFormat::Nbt,
+            (false, true) => NbtFormat::PartialNbt,
+        };
+        let data = json.unwrap_or_else(|| Value::String(data.to_string()));
+        Ok(Ingredient::Nbt(NbtIngredient {
+            loader,
+            format,
+            items: vec![item.trim().to_string()],
+            data,
+        }))
+    }
+
+    /// The id a recipe using this ingredient is named after, the first item or tag for anything matching several
+    pub fn id(&self) -> &str {
+        match self {
+            Ingredient::Item(id) | Ingredient::Tag(id) => id,
+            Ingredient::Alternatives(alternatives) => alternatives.first().map_or("", Ingredient::id),
+            Ingredient::Nbt(nbt) => nbt.items.first().map_or("", String::as_str),
+        }
+    }
+
+    /// Every item id mentioned, not including the contents of tags
+    pub fn item_ids(&self) -> Vec<&str> {
+        match self {
+            Ingredient::Item(id) => vec![id],
+            Ingredient::Tag(_) => vec![],
+            Ingredient::Alternatives(alternatives) => alternatives.iter().flat_map(Ingredient::item_ids).collect(),
+            Ingredient::Nbt(nbt) => nbt.items.iter().map(String::as_str).collect(),
+        }
+    }
+
+    /// Every tag id mentioned
+    pub fn tag_ids(&self) -> Vec<&str> {
+        match self {
+            Ingredient::Tag(id) => vec![id],
+            Ingredient::Alternatives(alternatives) => alternatives.iter().flat_map(Ingredient::tag_ids).collect(),
+            Ingredient::Item(_) | Ingredient::Nbt(_) => vec![],
+        }
+    }
+
+    /// The id in a form that can be used in a file name
+    pub fn file_name(&self) -> String {
+        match self {
+            Ingredient::Item(id) => id.replace([':', '/'], "."),
+            Ingredient::Tag(id) => format!("tag.{}", id.replace([':', '/'], ".")),
+            Ingredient::Alternatives(alternatives) => alternatives
+                .iter()
+                .map(Ingredient::file_name)
+                .collect::<Vec<_>>()
+                .join("-"),
+            Ingredient::Nbt(nbt) => format!(
+                "{}.{:08x}",
+                nbt.items.join("-").replace([':', '/'], "."),
+                nbt.data_hash()
+            ),
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Ingredient::Item(id) | Ingredient::Tag(id) => validate_resource_location(id),
+            Ingredient::Alternatives(alternatives) => {
+                !alternatives.is_empty()
+                    && alternatives
+                        .iter()
+                        .all(|a| !matches!(a, Ingredient::Alternatives(_)) && a.is_valid())
+            }
+            Ingredient::Nbt(nbt) => nbt.is_valid(),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            Ingredient::Item(id) => json!({ "item": id }),
+            Ingredient::Tag(id) => json!({ "tag": id }),
+            Ingredient::Alternatives(alternatives) => {
+                Value::Array(alternatives.iter().map(Ingredient::to_json).collect())
+            }
+            Ingredient::Nbt(nbt) => nbt.to_json(),
+        }
+    }
+
+    fn from_json(value: Value) -> Result<Ingredient, String> {
+        match value {
+            Value::Array(alternatives) => alternatives
+                .into_iter()
+                .map(Ingredient::from_json)
+                .collect::<Result<_, _>>()
+                .map(Ingredient::Alternatives),
+            Value::Object(mut object) => {
+                if let Some(ingredient_type) = object.get("type").and_then(Value::as_str) {
+                    NbtIngredient::from_json(ingredient_type, object.clone()).map(Ingredient::Nbt)
+                } else if let Some(Value::String(id)) = object.remove("item") {
+                    Ok(Ingredient::Item(id))
+                } else if let Some(Value::String(id)) = object.remove("tag") {
+                    Ok(Ingredient::Tag(id))
+                } else {
+                    Err(format!("Ingredient needs an item or tag: {}", Value::Object(object)))
+                }
+            }
+            //From 1.21.2 on ingredients can be written as just the id
+            Value::String(id) => Ok(match id.strip_prefix('#') {
+                Some(tag) => Ingredient::Tag(tag.to_string()),
+                None => Ingredient::Item(id),
+            }),
+            other => Err(format!("Ingredient should be an object, array or id: {other}")),
+        }
+    }
+}
+
+impl NbtIngredient {
+    /// Short hash of the NBT or components to tell apart ingredients for the same item
+    pub fn data_hash(&self) -> u32 {
+        stable_hash(&self.data.to_string()) as u32
+    }
+
+    pub fn is_valid(&self) -> bool {
+        let data_valid = match self.format {
+            NbtFormat::Nbt | NbtFormat::PartialNbt => self.data.is_string() || self.data.is_object(),
+            NbtFormat::Components { .. } => self.loader == Loader::NeoForge && self.data.is_object(),
+        };
+        let items_valid = match self.format {
+            NbtFormat::Nbt => self.items.len() == 1,
+            _ => !self.items.is_empty(),
+        };
+        data_valid && items_valid && self.items.iter().all(|item| validate_resource_location(item))
+    }
+
+    fn to_json(&self) -> Value {
+        let items = if self.items.len() == 1 {
+            json!(self.items[0])
+        } else {
+            json!(self.items)
+        };
+        match self.format {
+            NbtFormat::Nbt => json!({
+                "type": format!("{}:nbt", self.loader),
+                "item": items,
+                "nbt": self.data,
+            }),
+            NbtFormat::PartialNbt => {
+                let items_key = if self.items.len() == 1 { "item" } else { "items" };
+                let mut object = serde_json::Map::new();
+                object.insert("type".to_string(), json!(format!("{}:partial_nbt", self.loader)));
+                object.insert(items_key.to_string(), items);
+                object.insert("nbt".to_string(), self.data.clone());
+                Value::Object(object)
+            }
+            NbtFormat::Components { strict } => json!({
+                "type": format!("{}:components", self.loader),
+                "items": items,
+                "components": self.data,
+                "strict": strict,
+            }),
+        }
+    }
+
+    fn from_json(ingredient_type: &str, mut object: serde_json::Map<String, Value>) -> Result<NbtIngredient, String> {
+        let (loader, format) = match ingredient_type {
+            "forge:nbt" => (Loader::Forge, NbtFormat::Nbt),
+            "forge:partial_nbt" => (Loader::Forge, NbtFormat::PartialNbt),
+            "neoforge:nbt" => (Loader::NeoForge, NbtFormat::Nbt),
+            "neoforge:partial_nbt" => (Loader::NeoForge, NbtFormat::PartialNbt),
+            "neoforge:components" => {
+                let strict = object.get("strict").and_then(Value::as_bool).unwrap_or(false);
+                (Loader::NeoForge, NbtFormat::Components { strict })
+            }
+            _ => return Err(format!("Unsupported ingredient type {ingredient_type}")),
+        };
+        let items = match object.remove("items").or_else(|| object.remove("item")) {
+            Some(Value::String(item)) => vec![item],
+            Some(Value::Array(items)) => items
+                .into_iter()
+                .map(|item| match item {
+                    Value::String(item) => Ok(item),
+                    Value::Object(mut item) => match item.remove("item") {
+                        Some(Value::String(item)) => Ok(item),
+                        _ => Err(format!("Expected an item in {ingredient_type}")),
+                    },
+                    other => Err(format!("Expected an item in {ingredient_type}, got {other}")),
+                })
+                .collect::<Result<_, _>>()?,
+            _ => return Err(format!("{ingredient_type} ingredient has no items")),
+        };
+        let data_key = match format {
+            NbtFormat::Components { .. } => "components",
+            _ => "nbt",
+        };
+        let data = object
+            .remove(data_key)
+            .ok_or_else(|| format!("{ingredient_type} ingredient has no {data_key}"))?;
+        Ok(NbtIngredient {
+            loader,
+            format,
+            items,
+            data,
+        })
+    }
+}
+
+impl PartialOrd for NbtIngredient {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NbtIngredient {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.loader, self.format, &self.items)
+            .cmp(&(other.loader, other.format, &other.items))
+            .then_with(|| self.data.to_string().cmp(&other.data.to_string()))
+    }
+}
+
+/// The csv form that [`Ingredient::parse`] reads
+impl Display for Ingredient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Ingredient::Item(id) => write!(f, "{id}"),
+            Ingredient::Tag(id) => write!(f, "#{id}"),
+            Ingredient::Alternatives(alternatives) => {
+                let alternatives: Vec<_> = alternatives.iter().map(Ingredient::to_string).collect();
+                write!(f, "{}", alternatives.join("|"))
+            }
+            Ingredient::Nbt(nbt) => {
+                let partial = match nbt.format {
+                    NbtFormat::Nbt | NbtFormat::Components { strict: true } => "",
+                    NbtFormat::PartialNbt | NbtFormat::Components { strict: false } => "~",
+                };
+                let data = match &nbt.data {
+                    Value::String(snbt) => snbt.clone(),
+                    data => data.to_string(),
+                };
+                //Parsed back into one ingredient, except for exact NBT which is only for one item anyway
+                let items: Vec<_> = nbt.items.iter().map(|item| format!("{item}{partial}{data}")).collect();
+                write!(f, "{}", items.join("|"))
+            }
+        }
+    }
+}
+
+impl Serialize for Ingredient {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.to_json().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Ingredient {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ingredient::from_json(Value::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses the csv form, checking it's written back the same and survives being written as json
+    fn round_trip(csv: &str, loader: Loader) -> Ingredient {
+        let ingredient = Ingredient::parse(csv, loader).unwrap();
+        assert_eq!(ingredient.to_string(), csv);
+        let json = serde_json::to_value(&ingredient).unwrap();
+        assert_eq!(serde_json::from_value::<Ingredient>(json.clone()).unwrap(), ingredient);
+        assert_eq!(serde_json::to_value(Ingredient::parse(&ingredient.to_string(), loader).unwrap()).unwrap(), json);
+        ingredient
+    }
+
+    fn nbt(ingredient: Ingredient) -> NbtIngredient {
+        match ingredient {
+            Ingredient::Nbt(nbt) => nbt,
+            other => panic!("{other:?} isn't an NBT ingredient"),
+        }
+    }
+
+    #[test]
+    fn items_tags_and_alternatives() {
+        assert_eq!(round_trip("minecraft:stick", Loader::Forge), Ingredient::Item("minecraft:stick".to_string()));
+        assert_eq!(round_trip("#minecraft:logs", Loader::Forge), Ingredient::Tag("minecraft:logs".to_string()));
+        let alternatives = round_trip("minecraft:stick|#minecraft:logs", Loader::Forge);
+        assert!(matches!(alternatives, Ingredient::Alternatives(alternatives) if alternatives.len() == 2));
+    }
+
+    #[test]
+    fn forge_nbt() {
+        let exact = nbt(round_trip(r#"minecraft:potion{Potion:"minecraft:healing"}"#, Loader::Forge));
+        assert_eq!(exact.format, NbtFormat::Nbt);
+        assert_eq!(exact.data, Value::String(r#"{Potion:"minecraft:healing"}"#.to_string()));
+        let partial = nbt(round_trip(r#"minecraft:potion~{Potion:"minecraft:healing"}"#, Loader::Forge));
+        assert_eq!(partial.format, NbtFormat::PartialNbt);
+        //Json NBT stays json
+        let json = nbt(round_trip(r#"minecraft:potion{"Potion":"minecraft:healing"}"#, Loader::Forge));
+        assert_eq!(json.data, json!({ "Potion": "minecraft:healing" }));
+    }
+
+    #[test]
+    fn several_items_with_the_same_nbt() {
+        let partial = nbt(round_trip("minecraft:potion~{Potion:1}|minecraft:splash_potion~{Potion:1}", Loader::Forge));
+        assert_eq!(partial.items, ["minecraft:potion", "minecraft:splash_potion"]);
+        let components = nbt(round_trip(
+            r#"minecraft:potion{"minecraft:damage":1}|minecraft:splash_potion{"minecraft:damage":1}"#,
+            Loader::NeoForge,
+        ));
+        assert_eq!(components.items.len(), 2);
+        //Different NBT and exact NBT stay alternatives
+        let different = round_trip("minecraft:potion~{Potion:1}|minecraft:splash_potion~{Potion:2}", Loader::Forge);
+        assert!(matches!(different, Ingredient::Alternatives(_)));
+        let exact = round_trip("minecraft:potion{Potion:1}|minecraft:splash_potion{Potion:1}", Loader::Forge);
+        assert!(matches!(exact, Ingredient::Alternatives(_)));
+    }
+
+    #[test]
+    fn neoforge_components_and_nbt() {
+        const NEOFORGE: Loader = Loader::NeoForge;
+        let strict = nbt(round_trip(r#"minecraft:potion{"minecraft:potion_contents":{"potion":"a:b"}}"#, NEOFORGE));
+        assert_eq!(strict.format, NbtFormat::Components { strict: true });
+        let partial = nbt(round_trip(r#"minecraft:potion~{"minecraft:damage":1}"#, Loader::NeoForge));
+        assert_eq!(partial.format, NbtFormat::Components { strict: false });
+        let exact = nbt(round_trip(r#"minecraft:potion{Potion:"minecraft:healing"}"#, Loader::NeoForge));
+        assert_eq!((exact.loader, exact.format), (Loader::NeoForge, NbtFormat::Nbt));
+        assert_eq!(serde_json::to_value(Ingredient::Nbt(exact)).unwrap()["type"], "neoforge:nbt");
+        let partial = nbt(round_trip(r#"minecraft:potion~{"Potion":"minecraft:healing"}"#, Loader::NeoForge));
+        assert_eq!(partial.format, NbtFormat::PartialNbt);
+    }
+
+    #[test]
+    fn json_round_trips() {
+        for json in [
+            json!({ "type": "forge:nbt", "item": "minecraft:potion", "nbt": "{Potion:1}" }),
+            json!({
+                "type": "forge:partial_nbt",
+                "items": ["minecraft:potion", "minecraft:splash_potion"],
+                "nbt": { "Potion": 1 },
+            }),
+            json!({
+                "type": "neoforge:components",
+                "items": "minecraft:potion",
+                "components": { "minecraft:damage": 1 },
+                "strict": false,
+            }),
+            json!([{ "item": "minecraft:stick" }, { "tag": "minecraft:logs" }]),
+        ] {
+            let ingredient: Ingredient = serde_json::from_value(json.clone()).unwrap();
+            assert_eq!(serde_json::to_value(&ingredient).unwrap(), json);
+            let loader = match &ingredient {
+                Ingredient::Nbt(nbt) => nbt.loader,
+                _ => Loader::Forge,
+            };
+            assert_eq!(Ingredient::parse(&ingredient.to_string(), loader), Ok(ingredient));
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use std::{
+    collections::BTreeSet,
+    fmt::Display,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use crate::{did_you_mean, validate_resource_location, Datapack, Recipe};
+
+/// Item ids known to exist, to catch ids that are valid but don't belong to any item
+#[derive(Debug, Clone, Default)]
+pub struct ItemRegistry {
+    items: BTreeSet<String>,
+}
+
+/// An item in a recipe that isn't in the [`ItemRegistry`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownItem {
+    pub location: String,
+    pub item: String,
+    pub suggestion: Option<String>,
+}
+
+impl ItemRegistry {
+    pub fn new() -> ItemRegistry {
+        Default::default()
+    }
+
+    /// Adds items from a directory holding a datapack, a mod jar, or a text file with an item id on each line
+    pub fn load<P>(&mut self, path: P) -> std::io::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        if path.is_dir() {
+            self.add_datapack(&Datapack::load(path));
+            Ok(())
+        } else if path.extension().is_some_and(|e| e == "jar" || e == "zip") {
+            self.load_jar(path)
+        } else {
+            self.load_dump(path)
+        }
+    }
+
+    /// Adds the first id on every line of a registry dump, ignoring anything that isn't an id
+    pub fn load_dump<P>(&mut self, path: P) -> std::io::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            let id = line
+                .split(|c: char| c.is_whitespace() || c == ',' || c == '=')
+                .find(|part| !part.is_empty() && *part != "-");
+            if let Some(id) = id.filter(|id| validate_resource_location(id)) {
+                self.items.insert(id.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds every item with a model in a mod jar, which is every item the mod adds
+    pub fn load_jar<P>(&mut self, path: P) -> std::io::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let archive = zip::ZipArchive::new(File::open(path)?)?;
+        for name in archive.file_names() {
+            let Some(rest) = name.strip_prefix("assets/") else {
+                continue;
+            };
+            let Some((namespace, rest)) = rest.split_once('/') else {
+                continue;
+            };
+            let item = rest
+                .strip_prefix("models/item/")
+                .or_else(|| rest.strip_prefix("items/"))
+                .and_then(|item| item.strip_suffix(".json"));
+            if let Some(item) = item {
+                let id = format!("{namespace}:{item}");
+                if validate_resource_location(&id) {
+                    self.items.insert(id);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds every item used in a datapack's recipes, such as a previous version of this one
+    pub fn add_datapack(&mut self, datapack: &Datapack) {
+        for recipe in datapack.recipes.values() {
+            self.items.extend(recipe.item_ids().into_iter().map(str::to_string));
+        }
+    }
+
+    pub fn insert(&mut self, id: &str) {
+        self.items.insert(id.to_string());
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.items.contains(id)
+    }
+
+    /// Nothing can be checked against an empty registry, so it shouldn't report anything
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.items.iter().map(String::as_str)
+    }
+
+    /// The known item closest to an unknown one, if any are close enough to be a typo
+    pub fn suggest(&self, id: &str) -> Option<&str> {
+        did_you_mean(id, self.iter())
+    }
+
+    /// Items in a recipe at location that aren't known, or nothing if the registry is empty
+    pub fn check(&self, location: &str, recipe: &Recipe) -> Vec<UnknownItem> {
+        if self.is_empty() {
+            return vec![];
+        }
+        recipe.item_ids()
+            .into_iter()
+            .filter(|item| !self.contains(item))
+            .map(|item| UnknownItem {
+                location: location.to_string(),
+                item: item.to_string(),
+                suggestion: self.suggest(item).map(str::to_string),
+            })
+            .collect()
+    }
+}
+
+impl Display for UnknownItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unknown item {} in {}", self.item, self.location)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, ", did you mean {suggestion}?")?;
+        }
+        Ok(())
+    }
+}
@@ -1,28 +1,86 @@
 use std::{
-    collections::BTreeMap,
-    fs::{File, OpenOptions},
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    io::Read,
     path::Path, str::FromStr, fmt::Display,
 };
 
 use serde::{Deserialize, Serialize};
 use walkdir::WalkDir;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+mod balance;
+mod conditions;
+mod config;
+mod coverage;
+mod crafting;
+mod diagram;
+mod diff;
+mod estimate;
+mod exploits;
+mod expression;
+mod graph;
+mod grist;
+mod ingredient;
+mod items;
+mod mcmeta;
+mod merge;
+mod naming;
+mod priority;
+mod query;
+mod rollup;
+mod rules;
+mod solver;
+mod split;
+mod tags;
+
+pub use balance::{balance_report, sort_findings, BalanceFinding, BalanceSort, BalanceThresholds, FindingKind};
+pub use conditions::{Condition, Conditions, ALWAYS_LOADED};
+pub use config::{Config, CONFIG_FILE};
+pub use coverage::{CoverageReport, NamespaceCoverage};
+pub use crafting::{CraftingKind, CraftingRecipe};
+pub use diagram::{DiagramFilter, DiagramFormat};
+pub use diff::{Change, ChangeKind, DatapackDiff, DiffFormat};
+pub use estimate::{estimate_costs, recipe_cost, DraftCost, EstimateFormula, Rounding};
+pub use exploits::{Exploit, ExploitKind, ExploitStep, PricedItem};
+pub use expression::{evaluate_formulas, recipe_amounts, CostFormula, GristExpression};
+pub use graph::{node_name, AlchemyEdge, AlchemyGraph};
+pub use grist::{parse_color, GristRegistry, GristType, UnknownGrist, GRIST_TYPE_FILE, MINESTUCK_GRIST};
+pub use ingredient::{Ingredient, Loader, NbtFormat, NbtIngredient};
+pub use items::{ItemRegistry, UnknownItem};
+pub use mcmeta::{DescriptionMerge, Filter, FilterBlock, MCMeta, Pack};
+pub use merge::{
+    load_conflicts, save_conflicts, ConflictPart, MergeConflict, MergePolicy, MergeSide, ThreeWayConflict,
+    MERGE_CONFLICT_FILE,
+};
+pub use naming::RecipeNaming;
+pub use query::{RecipeIndex, RecipeQuery};
+pub use rollup::{add_grist, format_grist, grist_total, CostComparison, CostRollup, GristTotals};
+pub use priority::{default_priority, CostCandidate, CostResolution, CostResolver, GENERATED_PRIORITY};
+pub use rules::{apply_cost_rules, load_cost_rules, CostRule, RuleOutput};
+pub use solver::{parse_constraints, solve_costs, ConstraintProblem, CostConstraint, CostSolution, Relation};
+pub use split::{load_pack_mapping, CrossModRecipes, SplitSettings};
+pub use tags::{ItemTags, Tag, TagEntry, TagError};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct GristCostRecipe {
     pub priority: Option<i32>,
     pub ingredient: Ingredient,
     pub grist_cost: BTreeMap<String, i32>,
+    #[serde(flatten)]
+    pub conditions: Conditions,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CombinationRecipe {
     pub input1: Ingredient,
     pub input2: Ingredient,
     pub mode: CombinationMode,
     pub output: ResultItem,
+    #[serde(flatten)]
+    pub conditions: Conditions,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum CombinationMode {
     And,
@@ -41,6 +99,16 @@ impl FromStr for CombinationMode {
     }
 }
 
+impl CombinationMode {
+    /// The operator Minestuck's designix shows, `&&` or `||`
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            CombinationMode::And => "&&",
+            CombinationMode::Or => "||",
+        }
+    }
+}
+
 impl Display for CombinationMode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -50,12 +118,13 @@ impl Display for CombinationMode {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum Recipe {
     #[serde(rename = "minestuck:grist_cost")]
     GristCost(GristCostRecipe),
-    #[serde(rename = "minetuck:combination")]
+    //Older versions wrote the type with a typo
+    #[serde(rename = "minestuck:combination", alias = "minetuck:combination")]
     Combination(CombinationRecipe),
 }
 
@@ -66,16 +135,80 @@ impl Recipe {
             Recipe::Combination(recipe) => recipe.is_valid(),
         }
     }
+
+    pub fn conditions(&self) -> &Conditions {
+        match self {
+            Recipe::GristCost(recipe) => &recipe.conditions,
+            Recipe::Combination(recipe) => &recipe.conditions,
+        }
+    }
+
+    pub fn conditions_mut(&mut self) -> &mut Conditions {
+        match self {
+            Recipe::GristCost(recipe) => &mut recipe.conditions,
+            Recipe::Combination(recipe) => &mut recipe.conditions,
+        }
+    }
+
+    /// Every item id in the ingredients and result
+    pub fn item_ids(&self) -> Vec<&str> {
+        match self {
+            Recipe::GristCost(recipe) => recipe.ingredient.item_ids(),
+            Recipe::Combination(recipe) => {
+                let ResultItem::Item(output) = &recipe.output;
+                let mut ids = recipe.input1.item_ids();
+                ids.extend(recipe.input2.item_ids());
+                ids.push(output);
+                ids
+            }
+        }
+    }
+
+    /// Namespaces of every item and grist type used, other than ones that are always loaded
+    pub fn required_mods(&self) -> BTreeSet<&str> {
+        let mut ids = self.item_ids();
+        if let Recipe::GristCost(recipe) = self {
+            ids.extend(recipe.grist_cost.keys().map(String::as_str));
+        }
+        ids.into_iter()
+            .filter_map(|id| id.split_once(':').map(|(namespace, _)| namespace))
+            .filter(|namespace| !ALWAYS_LOADED.contains(namespace))
+            .collect()
+    }
+
+    /// Directory inside the datapack that recipes of this type are kept in
+    pub fn directory(&self) -> &'static str {
+        match self {
+            Recipe::GristCost(_) => "data/minestuck/recipes/grist_costs",
+            Recipe::Combination(_) => "data/minestuck/recipes/combination",
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum Ingredient {
-    Item(String),
-    Tag(String),
+/// The csv form `grist_json` and `alchemy_json` read
+impl Display for Recipe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Recipe::GristCost(recipe) => {
+                write!(f, "{}", recipe.ingredient)?;
+                for (grist, amount) in &recipe.grist_cost {
+                    write!(f, ",{}={amount}", grist.strip_prefix("minestuck:").unwrap_or(grist))?;
+                }
+                match recipe.priority {
+                    Some(GENERATED_PRIORITY) => Ok(()),
+                    Some(priority) => write!(f, ",priority={priority}"),
+                    None => write!(f, ",priority=default"),
+                }
+            }
+            Recipe::Combination(recipe) => {
+                let ResultItem::Item(output) = &recipe.output;
+                write!(f, "{},{},{},{output}", recipe.input1, recipe.mode.symbol(), recipe.input2)
+            }
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ResultItem {
     Item(String)
@@ -93,66 +226,51 @@ impl From<CombinationRecipe> for Recipe {
     }
 }
 
-#[derive(Default, Debug, Clone, Serialize, Deserialize)]
-pub struct MCMeta {
-    pub pack: Pack,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Pack {
-    pub pack_format: i32,
-    // It can also be a json object but I don't want to implement all that
-    pub description: String,
-}
-
-impl Default for Pack {
-    fn default() -> Self {
-        Self {
-            pack_format: 10,
-            description: "Created by Minestuck Datapack Generator".to_string(),
-        }
-    }
-}
-
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct Datapack {
     pub mcmeta: MCMeta,
     pub recipes: BTreeMap<String, Recipe>,
+    /// Grist types defined by this pack, by id
+    pub grist_types: BTreeMap<String, GristType>,
+    /// Item tags defined by this pack, by id
+    pub item_tags: BTreeMap<String, Tag>,
 }
 
 impl GristCostRecipe {
     pub fn is_valid(&self) -> bool {
-        match &self.ingredient {
-            Ingredient::Item(name) => {
-                if !validate_resource_location(name) {
-                    return false;
-                }
-            }
-            Ingredient::Tag(_) => {
-                eprintln!("Tags are unsupported");
-                return false;
-            }
-        }
-        self.grist_cost
-            .keys()
-            .all(|g| validate_resource_location(g))
+        self.ingredient.is_valid()
+            && self
+                .grist_cost
+                .keys()
+                .all(|g| validate_resource_location(g))
     }
 }
 
 impl CombinationRecipe {
-    pub fn is_valid(&self) -> bool {
-        match (&self.input1, &self.input2, &self.output) {
-            (Ingredient::Item(in1), Ingredient::Item(in2), ResultItem::Item(out)) => {
-                validate_resource_location(in1)
-                && validate_resource_location(in2)
-                && validate_resource_location(out)
-            },
-            (_, _, _) => {
-                eprintln!("Tags are unsupported");
-                false
-            }
+    /// The inputs in the order Minestuck treats as the same, since either input can go in either slot
+    pub fn sorted_inputs(&self) -> (&Ingredient, &Ingredient) {
+        if self.input1 <= self.input2 {
+            (&self.input1, &self.input2)
+        } else {
+            (&self.input2, &self.input1)
         }
     }
+
+    /// Name made from the inputs and mode, the same for any two recipes that combine the same things
+    pub fn inputs_key(&self) -> String {
+        let (first, second) = self.sorted_inputs();
+        format!("{}_{}_{}", first.file_name(), self.mode, second.file_name())
+    }
+
+    /// Whether both recipes would be used for the same pair of items
+    pub fn same_inputs(&self, other: &CombinationRecipe) -> bool {
+        self.mode == other.mode && self.sorted_inputs() == other.sorted_inputs()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        let ResultItem::Item(output) = &self.output;
+        self.input1.is_valid() && self.input2.is_valid() && validate_resource_location(output)
+    }
 }
 
 pub fn validate_resource_location(id: &str) -> bool {
@@ -166,6 +284,109 @@ pub fn validate_resource_location(id: &str) -> bool {
     }
 }
 
+/// Splits a line of csv on commas, except inside braces, brackets or quotes so NBT can contain them
+pub fn split_columns(line: &str) -> Vec<&str> {
+    split_outside_brackets(line, ',')
+        .into_iter()
+        .map(str::trim)
+        .collect()
+}
+
+/// Splits on a separator, except inside braces, brackets or quotes
+pub fn split_outside_brackets(s: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '{' | '[' if !quoted => depth += 1,
+            '}' | ']' if !quoted => depth -= 1,
+            c if c == separator && depth == 0 && !quoted => {
+                parts.push(&s[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+/// FNV-1a, for names that need to stay the same between runs and compiler versions
+pub(crate) fn stable_hash(text: &str) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Recipe id from a location in a datapack, `data/minestuck/recipes/grist_costs/stone` to `minestuck:grist_costs/stone`
+pub fn recipe_id(location: &str) -> Option<String> {
+    let (namespace, path) = location.strip_prefix("data/")?.split_once("/recipes/")?;
+    Some(format!("{namespace}:{path}"))
+}
+
+/// Location in a datapack from a recipe id, the reverse of [`recipe_id`]
+pub fn recipe_location(id: &str) -> Option<String> {
+    let (namespace, path) = id.split_once(':')?;
+    Some(format!("data/{namespace}/recipes/{path}"))
+}
+
+/// The candidate closest to id, if it's close enough that id is probably a typo of it
+pub fn did_you_mean<'a>(id: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let path_length = id.split_once(':').map_or(id, |(_, path)| path).chars().count();
+    //Anything is one edit away from a very short id
+    let max_distance = (path_length / 3).clamp(usize::from(path_length >= 3), 3);
+    if max_distance == 0 {
+        return None;
+    }
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.len().abs_diff(id.len()) <= max_distance)
+        .map(|candidate| (edit_distance(id, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance where swapping two neighbouring characters also counts as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![(0..=b.len()).collect::<Vec<_>>()];
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let substitution = rows[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            row[j] = substitution.min(rows[i - 1][j] + 1).min(row[j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+        rows.push(row);
+    }
+    rows[a.len()][b.len()]
+}
+
+/// The grist types in a datapack directory's grist type file, none if there isn't one
+fn read_grist_types(path: &Path) -> BTreeMap<String, GristType> {
+    let path = path.join(GRIST_TYPE_FILE);
+    match std::fs::read_to_string(&path) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            eprintln!("Failed to parse json at {path:?}, {e:?}");
+            BTreeMap::new()
+        }),
+        Err(_) => BTreeMap::new(),
+    }
+}
+
 /// Adds the "minestuck" namespace if none
 pub fn grist_resource(id: &str) -> String {
     if id.contains(':') {
@@ -180,40 +401,190 @@ impl Datapack {
         Default::default()
     }
 
+    /// Adds a recipe where the config's naming puts it, returning its location
+    ///
+    /// Conditions are switched to the config's loader, with mod_loaded conditions for every mod used if enabled.
+    /// Fails instead of replacing a recipe at the same location or a combination of the same inputs
+    pub fn add(&mut self, config: &Config, mut recipe: Recipe) -> Result<String, String> {
+        if config.mod_loaded_conditions {
+            let mods: Vec<String> = recipe.required_mods().into_iter().map(str::to_string).collect();
+            for mod_id in mods {
+                recipe.conditions_mut().require_mod(&mod_id);
+            }
+        }
+        recipe.conditions_mut().loader = config.loader;
+        let location = config
+            .naming
+            .location(&recipe)
+            .ok_or_else(|| format!("Can't name invalid recipe \"{recipe:?}\""))?;
+        if let Recipe::Combination(combination) = &recipe {
+            if let Some((other_location, other)) = self.find_combination(combination) {
+                return Err(if other.output == combination.output {
+                    format!("Duplicate of combination {other_location}")
+                } else {
+                    format!(
+                        "{} conflicts with {other_location}, which uses the same inputs",
+                        combination.inputs_key()
+                    )
+                });
+            }
+        }
+        if let Some(existing) = self.recipes.get(&location) {
+            return Err(if *existing == recipe {
+                format!("Duplicate recipe {location}")
+            } else {
+                format!("{location} already has a different recipe")
+            });
+        }
+        self.recipes.insert(location.clone(), recipe);
+        Ok(location)
+    }
+
+    /// Grist types used by grist cost recipes that aren't in the registry
+    pub fn unknown_grist(&self, registry: &GristRegistry) -> Vec<UnknownGrist> {
+        self.recipes
+            .iter()
+            .flat_map(|(location, recipe)| match recipe {
+                Recipe::GristCost(recipe) => registry.check(location, recipe),
+                Recipe::Combination(_) => vec![],
+            })
+            .collect()
+    }
+
+    /// Items used in recipes that aren't in the registry, nothing if the registry is empty
+    pub fn unknown_items(&self, registry: &ItemRegistry) -> Vec<UnknownItem> {
+        self.recipes
+            .iter()
+            .flat_map(|(location, recipe)| registry.check(location, recipe))
+            .collect()
+    }
+
+    /// Tags used by recipes that don't exist or can't be resolved
+    pub fn tag_problems(&self, tags: &ItemTags) -> Vec<String> {
+        self.recipes
+            .iter()
+            .flat_map(|(location, recipe)| tags.check(location, recipe))
+            .collect()
+    }
+
+    /// Removes a recipe from packs loaded before this one, such as Minestuck's own, by its id
+    pub fn remove_builtin(&mut self, id: &str) -> bool {
+        self.mcmeta.hide_recipe(id)
+    }
+
+    /// Finds a combination recipe that would be used for the same inputs
+    pub fn find_combination(&self, combination: &CombinationRecipe) -> Option<(&String, &CombinationRecipe)> {
+        self.recipes.iter().find_map(|(location, recipe)| match recipe {
+            Recipe::Combination(other) if other.same_inputs(combination) => Some((location, other)),
+            _ => None,
+        })
+    }
+
+    /// Pairs of locations of combination recipes that use the same inputs and mode
+    pub fn duplicate_combinations(&self) -> Vec<(String, String)> {
+        let mut by_inputs: BTreeMap<String, Vec<&String>> = BTreeMap::new();
+        for (location, recipe) in &self.recipes {
+            if let Recipe::Combination(combination) = recipe {
+                by_inputs.entry(combination.inputs_key()).or_default().push(location);
+            }
+        }
+        by_inputs
+            .into_values()
+            .flat_map(|locations| {
+                let first = locations[0];
+                locations[1..]
+                    .iter()
+                    .map(|other| (first.clone(), other.to_string()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Loads a datapack directory, or a zipped datapack if path is a file
     pub fn load<P>(path: P) -> Datapack
     where
         P: AsRef<Path>,
     {
         let path = path.as_ref();
+        if path.is_file() {
+            return Datapack::load_zip(path).unwrap_or_else(|e| {
+                eprintln!("Failed to read {path:?}, {e}");
+                Datapack::new()
+            });
+        }
         let mcmeta = match File::open(path.join("pack.mcmeta")) {
             Ok(file) => serde_json::from_reader(file).unwrap_or_default(),
             Err(_) => MCMeta::default(),
         };
-        let mut recipes = BTreeMap::new();
+        let mut datapack = Datapack {
+            mcmeta,
+            grist_types: read_grist_types(path),
+            ..Default::default()
+        };
         let data_path = path.join("data");
         if data_path.is_dir() {
             for dir_entry in WalkDir::new(data_path) {
                 let dir_entry = dir_entry.unwrap();
                 let recipe_path = dir_entry.path();
-                if recipe_path.extension().map_or(false, |e| e == "json") {
+                if recipe_path.extension().is_some_and(|e| e == "json") {
                     let location = recipe_path
                         .strip_prefix(path)
                         .unwrap()
-                        .file_stem()
-                        .unwrap()
+                        .with_extension("")
                         .to_string_lossy()
-                        .to_string();
-                    let file = File::open(recipe_path).unwrap();
-                    match serde_json::from_reader::<_, Recipe>(file) {
-                        Ok(recipe) => { recipes.insert(location, recipe); }
-                        Err(e) => eprintln!("Failed to parse json at {recipe_path:?}, {e:?}")
+                        .replace('\\', "/");
+                    let json = std::fs::read_to_string(recipe_path).unwrap();
+                    if let Err(e) = datapack.add_json(location, &json) {
+                        eprintln!("Failed to parse json at {recipe_path:?}, {e:?}");
                     }
                 }
             }
         } else {
             println!("No existing recipes");
         }
-        Datapack { mcmeta, recipes }
+        datapack
+    }
+
+    /// Loads a zipped datapack, with pack.mcmeta and data at the top of the zip
+    pub fn load_zip<P>(path: P) -> std::io::Result<Datapack>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+        let mut datapack = Datapack::new();
+        let names: Vec<String> = archive.file_names().map(str::to_string).collect();
+        for name in names {
+            let mut json = String::new();
+            if name == "pack.mcmeta" {
+                archive.by_name(&name)?.read_to_string(&mut json)?;
+                datapack.mcmeta = serde_json::from_str(&json).unwrap_or_default();
+            } else if name == GRIST_TYPE_FILE {
+                archive.by_name(&name)?.read_to_string(&mut json)?;
+                match serde_json::from_str(&json) {
+                    Ok(grist_types) => datapack.grist_types = grist_types,
+                    Err(e) => eprintln!("Failed to parse json at {name} in {path:?}, {e:?}"),
+                }
+            } else if let Some(location) = name.strip_suffix(".json").filter(|name| name.starts_with("data/")) {
+                archive.by_name(&name)?.read_to_string(&mut json)?;
+                if let Err(e) = datapack.add_json(location.to_string(), &json) {
+                    eprintln!("Failed to parse json at {name} in {path:?}, {e:?}");
+                }
+            }
+        }
+        Ok(datapack)
+    }
+
+    /// Adds an item tag or recipe from a file in the data directory, depending on where it is
+    fn add_json(&mut self, location: String, json: &str) -> serde_json::Result<()> {
+        if let Some(id) = Tag::id(&location) {
+            self.item_tags.insert(id, serde_json::from_str(json)?);
+        } else if location.contains("/tags/") {
+            //Tags for anything other than items aren't used
+        } else {
+            self.recipes.insert(location, serde_json::from_str(json)?);
+        }
+        Ok(())
     }
 
     pub fn save<P>(&self, path: P)
@@ -222,12 +593,17 @@ impl Datapack {
     {
         let path = path.as_ref();
         {
-            //Don't replace existing pack.mcmeta
-            if let Ok(file) = OpenOptions::new()
-                .create_new(true)
-                .write(true)
-                .open(path.join("pack.mcmeta"))
-            {
+            let mcmeta_path = path.join("pack.mcmeta");
+            //Don't replace a pack.mcmeta we can't read, everything in one we can read is kept
+            let unreadable = File::open(&mcmeta_path)
+                .is_ok_and(|file| serde_json::from_reader::<_, MCMeta>(file).is_err());
+            if unreadable {
+                if self.mcmeta.filter.is_some() {
+                    eprintln!("Couldn't parse {mcmeta_path:?} so the filter hiding recipes wasn't written");
+                }
+            } else {
+                std::fs::create_dir_all(path).unwrap();
+                let file = File::create(mcmeta_path).unwrap();
                 serde_json::to_writer_pretty(file, &self.mcmeta).unwrap();
             }
         }
@@ -236,10 +612,67 @@ impl Datapack {
         //It'll be confusing why deleting an entry doesn't remove the recipe so let's just start fresh
         let _ = std::fs::remove_dir_all(grist_costs_path);
         let _ = std::fs::remove_dir_all(combination_recipes_path);
+        let _ = std::fs::remove_file(path.join(GRIST_TYPE_FILE));
+        if let Ok(namespaces) = std::fs::read_dir(path.join("data")) {
+            for namespace in namespaces.flatten() {
+                let _ = std::fs::remove_dir_all(namespace.path().join("tags/items"));
+            }
+        }
+        self.write_recipes(path);
+        self.write_grist_types(path);
+        self.write_item_tags(path);
+    }
+
+    /// Writes every valid item tag under path, leaving anything already there alone
+    pub fn write_item_tags<P>(&self, path: P)
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        for (id, tag) in &self.item_tags {
+            match Tag::location(id) {
+                Some(location) if validate_resource_location(id) && tag.is_valid() => {
+                    let tag_path = path.join(format!("{location}.json"));
+                    std::fs::create_dir_all(tag_path.parent().unwrap()).unwrap();
+                    let file = File::create(tag_path).unwrap();
+                    serde_json::to_writer_pretty(file, &tag).unwrap();
+                }
+                _ => eprintln!("Invalid tag {id}: \"{tag:#?}\""),
+            }
+        }
+    }
 
+    /// Writes every valid grist type to the grist type file under path, keeping the ones already there that aren't
+    /// replaced
+    pub fn write_grist_types<P>(&self, path: P)
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let mut grist_types = read_grist_types(path);
+        for (id, grist_type) in &self.grist_types {
+            if validate_resource_location(id) && grist_type.is_valid() {
+                grist_types.insert(id.clone(), grist_type.clone());
+            } else {
+                eprintln!("Invalid grist type {id}: \"{grist_type:#?}\"");
+            }
+        }
+        if !grist_types.is_empty() {
+            std::fs::create_dir_all(path).unwrap();
+            let file = File::create(path.join(GRIST_TYPE_FILE)).unwrap();
+            serde_json::to_writer_pretty(file, &grist_types).unwrap();
+        }
+    }
+
+    /// Writes every valid recipe under path, leaving anything already there alone
+    pub fn write_recipes<P>(&self, path: P)
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
         for (location, recipe) in &self.recipes {
             if recipe.is_valid() {
-                let recipe_path = path.join(&format!("{location}.json"));
+                let recipe_path = path.join(format!("{location}.json"));
                 std::fs::create_dir_all(recipe_path.parent().unwrap()).unwrap();
                 let file = File::create(recipe_path).unwrap();
                 serde_json::to_writer_pretty(file, &recipe).unwrap();
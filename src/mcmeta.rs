@@ -0,0 +1,166 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MCMeta {
+    pub pack: Pack,
+    /// Files to hide from packs loaded below this one, since 1.19
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<Filter>,
+    /// Anything else in the file, kept as is so it isn't lost when saving
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pack {
+    pub pack_format: i32,
+    // It can also be a json object but I don't want to implement all that
+    pub description: String,
+}
+
+impl Default for Pack {
+    fn default() -> Self {
+        Self {
+            pack_format: 10,
+            description: "Created by Minestuck Datapack Generator".to_string(),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Filter {
+    pub block: Vec<FilterBlock>,
+}
+
+/// Hides files whose namespace and path inside it both fully match these regexes, a missing one matches anything
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct FilterBlock {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+impl FilterBlock {
+    /// Block for exactly one recipe, from an id like `minestuck:grist_costs/stone`
+    pub fn recipe(id: &str) -> Option<FilterBlock> {
+        let (namespace, path) = id.split_once(':')?;
+        Some(FilterBlock {
+            namespace: Some(escape_regex(namespace)),
+            path: Some(escape_regex(&format!("recipes/{path}.json"))),
+        })
+    }
+}
+
+impl MCMeta {
+    /// Hides a recipe in packs loaded below, which is how a recipe built into Minestuck gets removed
+    ///
+    /// Returns false if it was already hidden
+    pub fn hide_recipe(&mut self, id: &str) -> bool {
+        let Some(block) = FilterBlock::recipe(id) else {
+            return false;
+        };
+        let filter = self.filter.get_or_insert_with(Default::default);
+        if filter.block.contains(&block) {
+            false
+        } else {
+            filter.block.push(block);
+            true
+        }
+    }
+
+    /// Stops hiding a recipe hidden by [`MCMeta::hide_recipe`], returning false if it wasn't hidden
+    pub fn unhide_recipe(&mut self, id: &str) -> bool {
+        let (Some(block), Some(filter)) = (FilterBlock::recipe(id), &mut self.filter) else {
+            return false;
+        };
+        let count = filter.block.len();
+        filter.block.retain(|b| *b != block);
+        let removed = filter.block.len() != count;
+        if filter.block.is_empty() {
+            self.filter = None;
+        }
+        removed
+    }
+
+    /// Whether [`MCMeta::hide_recipe`] has hidden this recipe, other filter entries aren't checked
+    pub fn hides_recipe(&self, id: &str) -> bool {
+        match (FilterBlock::recipe(id), &self.filter) {
+            (Some(block), Some(filter)) => filter.block.contains(&block),
+            _ => false,
+        }
+    }
+}
+
+/// How the descriptions of merged packs are combined
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum DescriptionMerge {
+    /// The first pack's
+    #[default]
+    Left,
+    /// The second pack's
+    Right,
+    /// Both, on separate lines, unless they're the same
+    Join,
+    /// Replaced with this
+    Text(String),
+}
+
+impl FromStr for DescriptionMerge {
+    type Err = String;
+
+    /// `left`, `right`, `join`, or anything else to use as the description
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "left" => DescriptionMerge::Left,
+            "right" => DescriptionMerge::Right,
+            "join" => DescriptionMerge::Join,
+            _ => DescriptionMerge::Text(s.to_string()),
+        })
+    }
+}
+
+impl MCMeta {
+    /// Combines two packs' metadata, using the newer pack format and hiding everything either one hides
+    ///
+    /// Other fields come from the first pack if both have them
+    pub fn merge(&self, other: &MCMeta, description: &DescriptionMerge) -> MCMeta {
+        let description = match description {
+            DescriptionMerge::Left => self.pack.description.clone(),
+            DescriptionMerge::Right => other.pack.description.clone(),
+            DescriptionMerge::Join if self.pack.description == other.pack.description => self.pack.description.clone(),
+            DescriptionMerge::Join => format!("{}\n{}", self.pack.description, other.pack.description),
+            DescriptionMerge::Text(text) => text.clone(),
+        };
+        let mut blocks: Vec<FilterBlock> = vec![];
+        for block in self.filter.iter().chain(&other.filter).flat_map(|filter| &filter.block) {
+            if !blocks.contains(block) {
+                blocks.push(block.clone());
+            }
+        }
+        let mut extra = other.extra.clone();
+        extra.extend(self.extra.clone());
+        MCMeta {
+            pack: Pack {
+                pack_format: self.pack.pack_format.max(other.pack.pack_format),
+                description,
+            },
+            filter: (!blocks.is_empty()).then_some(Filter { block: blocks }),
+            extra,
+        }
+    }
+}
+
+fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
@@ -0,0 +1,588 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    fs::File,
+    path::Path,
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{default_priority, Datapack, DescriptionMerge, GristCostRecipe, Recipe, Tag, TagEntry, GRIST_TYPE_FILE};
+
+/// What to do when two packs being merged have different versions of something
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Fail, listing every conflict
+    #[default]
+    Error,
+    /// Keep the first pack's
+    PreferLeft,
+    /// Keep the second pack's
+    PreferRight,
+    /// Keep the grist cost with the higher priority, the first pack's if they're the same or it's a combination
+    HigherPriority,
+    /// Add the grist costs together
+    SumGrist,
+    /// Take the larger amount of each grist type
+    MaxGrist,
+}
+
+impl FromStr for MergePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(MergePolicy::Error),
+            "left" => Ok(MergePolicy::PreferLeft),
+            "right" => Ok(MergePolicy::PreferRight),
+            "priority" => Ok(MergePolicy::HigherPriority),
+            "sum" => Ok(MergePolicy::SumGrist),
+            "max" => Ok(MergePolicy::MaxGrist),
+            _ => Err(format!("Unknown merge policy \"{s}\", expected error, left, right, priority, sum or max")),
+        }
+    }
+}
+
+/// Something both packs have different versions of, and what was kept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeConflict {
+    /// What the conflict is over, like `grist cost for minecraft:stone`
+    pub name: String,
+    pub left_location: String,
+    pub right_location: String,
+    /// None if the policy is to fail
+    pub resolution: Option<String>,
+}
+
+/// What two recipes conflict over, so ones at different locations are still found
+fn recipe_key(recipe: &Recipe) -> String {
+    match recipe {
+        Recipe::GristCost(recipe) => format!("grist cost for {}", recipe.ingredient),
+        Recipe::Combination(recipe) => {
+            let (first, second) = recipe.sorted_inputs();
+            format!("combination of {first} {} {second}", recipe.mode.symbol())
+        }
+    }
+}
+
+/// Left out priorities count as Minestuck's default for a single item
+fn priority(recipe: &GristCostRecipe) -> i32 {
+    recipe.priority.unwrap_or(default_priority(1))
+}
+
+fn combine_grist(left: &GristCostRecipe, right: &GristCostRecipe, combine: impl Fn(i32, i32) -> i32) -> Recipe {
+    let mut grist_cost = left.grist_cost.clone();
+    for (grist, amount) in &right.grist_cost {
+        grist_cost
+            .entry(grist.clone())
+            .and_modify(|left| *left = combine(*left, *amount))
+            .or_insert(*amount);
+    }
+    GristCostRecipe {
+        priority: left.priority.max(right.priority),
+        grist_cost,
+        ..left.clone()
+    }
+    .into()
+}
+
+fn resolve(left: &Recipe, right: &Recipe, policy: MergePolicy) -> Option<(Recipe, &'static str)> {
+    let grist_costs = match (left, right) {
+        (Recipe::GristCost(left), Recipe::GristCost(right)) => Some((left, right)),
+        _ => None,
+    };
+    Some(match (policy, grist_costs) {
+        (MergePolicy::Error, _) => return None,
+        (MergePolicy::PreferLeft, _) => (left.clone(), "kept the left one"),
+        (MergePolicy::PreferRight, _) => (right.clone(), "kept the right one"),
+        (MergePolicy::HigherPriority, Some((left_cost, right_cost))) => {
+            if priority(right_cost) > priority(left_cost) {
+                (right.clone(), "kept the right one, it has a higher priority")
+            } else {
+                (left.clone(), "kept the left one, its priority is at least as high")
+            }
+        }
+        (MergePolicy::SumGrist, Some((left, right))) => (combine_grist(left, right, i32::saturating_add), "added the grist together"),
+        (MergePolicy::MaxGrist, Some((left, right))) => (combine_grist(left, right, i32::max), "took the larger amount of each grist type"),
+        (_, None) => (left.clone(), "kept the left one, only grist costs can be combined"),
+    })
+}
+
+impl Datapack {
+    /// Combines this pack with another, handling anything both have different versions of with the policy
+    ///
+    /// Recipes conflict if they're at the same location, or are grist costs for the same ingredient or combinations
+    /// of the same inputs, in which case the left one's location is kept. Tags conflicting under the priority, sum
+    /// and max policies get the entries from both. Fails with every conflict if the policy is to fail
+    pub fn merge(
+        &self,
+        other: &Datapack,
+        policy: MergePolicy,
+        description: &DescriptionMerge,
+    ) -> Result<(Datapack, Vec<MergeConflict>), Vec<MergeConflict>> {
+        let mut merged = self.clone();
+        merged.mcmeta = self.mcmeta.merge(&other.mcmeta, description);
+        let mut conflicts = vec![];
+        let mut by_key: BTreeMap<String, String> = self
+            .recipes
+            .iter()
+            .map(|(location, recipe)| (recipe_key(recipe), location.clone()))
+            .collect();
+        for (location, recipe) in &other.recipes {
+            let key = recipe_key(recipe);
+            let existing = if merged.recipes.contains_key(location) {
+                Some(location.clone())
+            } else {
+                by_key.get(&key).cloned()
+            };
+            let Some(existing_location) = existing else {
+                merged.recipes.insert(location.clone(), recipe.clone());
+                by_key.insert(key, location.clone());
+                continue;
+            };
+            let existing = &merged.recipes[&existing_location];
+            if existing == recipe {
+                continue;
+            }
+            let resolved = resolve(existing, recipe, policy);
+            conflicts.push(MergeConflict {
+                name: key,
+                left_location: existing_location.clone(),
+                right_location: location.clone(),
+                resolution: resolved.as_ref().map(|(_, resolution)| resolution.to_string()),
+            });
+            if let Some((recipe, _)) = resolved {
+                merged.recipes.insert(existing_location, recipe);
+            }
+        }
+
+        for (id, grist_type) in &other.grist_types {
+            match merged.grist_types.get(id) {
+                Some(existing) if existing != grist_type => {
+                    let resolution = match policy {
+                        MergePolicy::Error => None,
+                        MergePolicy::PreferRight => {
+                            merged.grist_types.insert(id.clone(), grist_type.clone());
+                            Some("kept the right one")
+                        }
+                        _ => Some("kept the left one"),
+                    };
+                    let location = GRIST_TYPE_FILE.to_string();
+                    conflicts.push(MergeConflict {
+                        name: format!("grist type {id}"),
+                        left_location: location.clone(),
+                        right_location: location,
+                        resolution: resolution.map(str::to_string),
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    merged.grist_types.insert(id.clone(), grist_type.clone());
+                }
+            }
+        }
+
+        for (id, tag) in &other.item_tags {
+            let Some(existing) = merged.item_tags.get_mut(id) else {
+                merged.item_tags.insert(id.clone(), tag.clone());
+                continue;
+            };
+            if existing == tag {
+                continue;
+            }
+            let resolution = match policy {
+                MergePolicy::Error => None,
+                MergePolicy::PreferLeft => Some("kept the left one"),
+                MergePolicy::PreferRight => {
+                    *existing = tag.clone();
+                    Some("kept the right one")
+                }
+                _ => {
+                    for entry in &tag.values {
+                        if !existing.values.contains(entry) {
+                            existing.values.push(entry.clone());
+                        }
+                    }
+                    existing.replace |= tag.replace;
+                    Some("used the entries from both")
+                }
+            };
+            let location = Tag::location(id).unwrap_or_else(|| id.clone());
+            conflicts.push(MergeConflict {
+                name: format!("item tag {id}"),
+                left_location: location.clone(),
+                right_location: location,
+                resolution: resolution.map(str::to_string),
+            });
+        }
+
+        if policy == MergePolicy::Error && !conflicts.is_empty() {
+            Err(conflicts)
+        } else {
+            Ok((merged, conflicts))
+        }
+    }
+}
+
+impl Display for MergeConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if self.left_location == self.right_location {
+            write!(f, " differs at {}", self.left_location)?;
+        } else {
+            write!(f, " differs between {} and {}", self.left_location, self.right_location)?;
+        }
+        if let Some(resolution) = &self.resolution {
+            write!(f, ", {resolution}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Where [`Datapack::three_way_merge`]'s conflicts are written for the GUI to resolve, inside the merged pack so
+/// they stay with it wherever it's written. Minecraft ignores it
+pub const MERGE_CONFLICT_FILE: &str = "merge_conflicts.json";
+
+/// One of the packs in a three-way merge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeSide {
+    Base,
+    Ours,
+    Theirs,
+}
+
+/// Which part of a recipe, grist type, tag or pack.mcmeta a conflict is over
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPart {
+    /// The whole recipe, if it was deleted on one side or its ingredient or conditions conflict
+    Recipe,
+    Priority,
+    /// One grist type's amount in a grist cost
+    Grist(String),
+    GristType,
+    ItemTag,
+    Mcmeta,
+}
+
+/// Something both sides changed differently since the base, None for each side that doesn't have it
+///
+/// The merged pack has our version until it's resolved
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThreeWayConflict {
+    /// What the conflict is over, like `grist cost for minecraft:stone`
+    pub name: String,
+    /// The recipe's location, or the grist type or tag's id
+    pub id: String,
+    pub part: ConflictPart,
+    pub base: Option<Value>,
+    pub ours: Option<Value>,
+    pub theirs: Option<Value>,
+}
+
+impl ThreeWayConflict {
+    fn new<T: Serialize>(
+        name: String,
+        id: &str,
+        part: ConflictPart,
+        [base, ours, theirs]: [Option<&T>; 3],
+    ) -> ThreeWayConflict {
+        let value = |value: Option<&T>| value.map(|value| serde_json::to_value(value).unwrap());
+        ThreeWayConflict {
+            name,
+            id: id.to_string(),
+            part,
+            base: value(base),
+            ours: value(ours),
+            theirs: value(theirs),
+        }
+    }
+
+    pub fn value(&self, side: MergeSide) -> Option<&Value> {
+        match side {
+            MergeSide::Base => self.base.as_ref(),
+            MergeSide::Ours => self.ours.as_ref(),
+            MergeSide::Theirs => self.theirs.as_ref(),
+        }
+    }
+}
+
+/// Reads the conflicts left by a three-way merge in a pack directory, none if there's no conflict file
+pub fn load_conflicts<P>(pack: P) -> Result<Vec<ThreeWayConflict>, String>
+where
+    P: AsRef<Path>,
+{
+    let path = pack.as_ref().join(MERGE_CONFLICT_FILE);
+    let path = path.as_path();
+    match std::fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json).map_err(|e| format!("Failed to parse {path:?}, {e}")),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+        Err(e) => Err(format!("Failed to read {path:?}, {e}")),
+    }
+}
+
+/// Writes the conflicts left to resolve into a pack directory, removing the file once there are none
+pub fn save_conflicts<P>(pack: P, conflicts: &[ThreeWayConflict])
+where
+    P: AsRef<Path>,
+{
+    let path = pack.as_ref().join(MERGE_CONFLICT_FILE);
+    if conflicts.is_empty() {
+        let _ = std::fs::remove_file(path);
+    } else {
+        let file = File::create(path).unwrap();
+        serde_json::to_writer_pretty(file, conflicts).unwrap();
+    }
+}
+
+/// Whichever side changed the value since the base, or None if both changed it differently
+fn merge_value<T: PartialEq + Clone>([base, ours, theirs]: [Option<&T>; 3]) -> Option<Option<T>> {
+    if ours == theirs || base == theirs {
+        Some(ours.cloned())
+    } else if base == ours {
+        Some(theirs.cloned())
+    } else {
+        None
+    }
+}
+
+/// Merges each field of grist costs both sides changed, so changes to different grist types don't conflict
+fn merge_grist_costs(
+    name: &str,
+    location: &str,
+    [base, ours, theirs]: [&GristCostRecipe; 3],
+    conflicts: &mut Vec<ThreeWayConflict>,
+) -> Option<GristCostRecipe> {
+    let ingredient = merge_value([Some(&base.ingredient), Some(&ours.ingredient), Some(&theirs.ingredient)])?;
+    let conditions = merge_value([Some(&base.conditions), Some(&ours.conditions), Some(&theirs.conditions)])?;
+    let mut merged = GristCostRecipe {
+        ingredient: ingredient.unwrap(),
+        conditions: conditions.unwrap(),
+        ..ours.clone()
+    };
+    let priorities = [Some(&base.priority), Some(&ours.priority), Some(&theirs.priority)];
+    match merge_value(priorities) {
+        Some(priority) => merged.priority = priority.unwrap(),
+        None => conflicts.push(ThreeWayConflict::new(name.to_string(), location, ConflictPart::Priority, priorities)),
+    }
+    let grist_types: BTreeSet<&String> =
+        base.grist_cost.keys().chain(ours.grist_cost.keys()).chain(theirs.grist_cost.keys()).collect();
+    for grist in grist_types {
+        let amounts = [base.grist_cost.get(grist), ours.grist_cost.get(grist), theirs.grist_cost.get(grist)];
+        match merge_value(amounts) {
+            Some(Some(amount)) => {
+                merged.grist_cost.insert(grist.clone(), amount);
+            }
+            Some(None) => {
+                merged.grist_cost.remove(grist);
+            }
+            None => conflicts.push(ThreeWayConflict::new(
+                name.to_string(),
+                location,
+                ConflictPart::Grist(grist.clone()),
+                amounts,
+            )),
+        }
+    }
+    Some(merged)
+}
+
+/// Keeps the entries neither side removed, and adds the ones either side added
+fn merge_tags([base, ours, theirs]: [&Tag; 3]) -> Tag {
+    let mut values: Vec<TagEntry> = ours
+        .values
+        .iter()
+        .filter(|entry| !base.values.contains(entry) || theirs.values.contains(entry))
+        .cloned()
+        .collect();
+    for entry in &theirs.values {
+        if !base.values.contains(entry) && !values.contains(entry) {
+            values.push(entry.clone());
+        }
+    }
+    Tag {
+        replace: merge_value([Some(&base.replace), Some(&ours.replace), Some(&theirs.replace)])
+            .flatten()
+            .unwrap_or(ours.replace),
+        values,
+    }
+}
+
+impl Datapack {
+    /// Merges the changes made to a base pack on two sides, like git does for files
+    ///
+    /// Recipes, grist types and tags are matched by location or id. Grist costs changed on both sides are merged
+    /// a grist type at a time, and tags an entry at a time. What both sides changed differently is returned as
+    /// conflicts, the merged pack keeping our version
+    pub fn three_way_merge(base: &Datapack, ours: &Datapack, theirs: &Datapack) -> (Datapack, Vec<ThreeWayConflict>) {
+        let mut conflicts = vec![];
+        let mut merged = ours.clone();
+
+        let mcmetas = [Some(&base.mcmeta), Some(&ours.mcmeta), Some(&theirs.mcmeta)];
+        match merge_value(mcmetas) {
+            Some(mcmeta) => merged.mcmeta = mcmeta.unwrap(),
+            None => conflicts.push(ThreeWayConflict::new(
+                "pack.mcmeta".to_string(),
+                "pack.mcmeta",
+                ConflictPart::Mcmeta,
+                mcmetas,
+            )),
+        }
+
+        let locations: BTreeSet<&String> =
+            base.recipes.keys().chain(ours.recipes.keys()).chain(theirs.recipes.keys()).collect();
+        for location in locations {
+            let recipes = [base.recipes.get(location), ours.recipes.get(location), theirs.recipes.get(location)];
+            let recipe = match merge_value(recipes) {
+                Some(recipe) => recipe,
+                None => {
+                    let name = recipes.iter().flatten().map(|recipe| recipe_key(recipe)).next().unwrap();
+                    let grist_costs = match recipes {
+                        [Some(Recipe::GristCost(base)), Some(Recipe::GristCost(ours)), Some(Recipe::GristCost(theirs))] => {
+                            merge_grist_costs(&name, location, [base, ours, theirs], &mut conflicts)
+                        }
+                        _ => None,
+                    };
+                    if grist_costs.is_none() {
+                        conflicts.push(ThreeWayConflict::new(name, location, ConflictPart::Recipe, recipes));
+                    }
+                    grist_costs.map(Recipe::from).or_else(|| recipes[1].cloned())
+                }
+            };
+            match recipe {
+                Some(recipe) => merged.recipes.insert(location.clone(), recipe),
+                None => merged.recipes.remove(location),
+            };
+        }
+
+        let ids: BTreeSet<&String> =
+            base.grist_types.keys().chain(ours.grist_types.keys()).chain(theirs.grist_types.keys()).collect();
+        for id in ids {
+            let grist_types = [base.grist_types.get(id), ours.grist_types.get(id), theirs.grist_types.get(id)];
+            match merge_value(grist_types) {
+                Some(Some(grist_type)) => merged.grist_types.insert(id.clone(), grist_type),
+                Some(None) => merged.grist_types.remove(id),
+                None => {
+                    let name = format!("grist type {id}");
+                    conflicts.push(ThreeWayConflict::new(name, id, ConflictPart::GristType, grist_types));
+                    None
+                }
+            };
+        }
+
+        let ids: BTreeSet<&String> =
+            base.item_tags.keys().chain(ours.item_tags.keys()).chain(theirs.item_tags.keys()).collect();
+        for id in ids {
+            let tags = [base.item_tags.get(id), ours.item_tags.get(id), theirs.item_tags.get(id)];
+            let tag = match (merge_value(tags), tags) {
+                (Some(tag), _) => tag,
+                //Both sides added a tag with different entries, so it's merged as if the base had an empty one
+                (None, [None, Some(ours), Some(theirs)]) => Some(merge_tags([&Tag::default(), ours, theirs])),
+                (None, [Some(base), Some(ours), Some(theirs)]) => Some(merge_tags([base, ours, theirs])),
+                (None, _) => {
+                    let name = format!("item tag {id}");
+                    conflicts.push(ThreeWayConflict::new(name, id, ConflictPart::ItemTag, tags));
+                    tags[1].cloned()
+                }
+            };
+            match tag {
+                Some(tag) => merged.item_tags.insert(id.clone(), tag),
+                None => merged.item_tags.remove(id),
+            };
+        }
+
+        (merged, conflicts)
+    }
+}
+
+impl Display for ThreeWayConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        match &self.part {
+            ConflictPart::Priority => write!(f, ", priority")?,
+            ConflictPart::Grist(grist) => write!(f, ", {}", grist.strip_prefix("minestuck:").unwrap_or(grist))?,
+            _ => {}
+        }
+        //Whole recipes are easier to read the way the other tools show them
+        let value = |value: Option<&Value>| match value {
+            Some(value) => match serde_json::from_value::<Recipe>(value.clone()) {
+                Ok(recipe) if self.part == ConflictPart::Recipe => recipe.to_string(),
+                _ => value.to_string(),
+            },
+            None => "none".to_string(),
+        };
+        write!(
+            f,
+            ": base {}, ours {}, theirs {}",
+            value(self.base.as_ref()),
+            value(self.ours.as_ref()),
+            value(self.theirs.as_ref())
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GristCostRecipe, Ingredient};
+
+    const STONE: &str = "data/minestuck/recipes/grist_costs/minecraft/stone";
+
+    fn pack(grist: &[(&str, i32)]) -> Datapack {
+        let recipe = GristCostRecipe {
+            priority: Some(101),
+            ingredient: Ingredient::Item("minecraft:stone".to_string()),
+            grist_cost: grist.iter().map(|(grist, amount)| (format!("minestuck:{grist}"), *amount)).collect(),
+            conditions: Default::default(),
+        };
+        let mut pack = Datapack::new();
+        pack.recipes.insert(STONE.to_string(), recipe.into());
+        pack
+    }
+
+    fn grist_cost(pack: &Datapack) -> &BTreeMap<String, i32> {
+        match &pack.recipes[STONE] {
+            Recipe::GristCost(recipe) => &recipe.grist_cost,
+            recipe => panic!("Expected a grist cost, got {recipe:?}"),
+        }
+    }
+
+    #[test]
+    fn different_grist_types_merge() {
+        let base = pack(&[("build", 2), ("shale", 1)]);
+        let ours = pack(&[("build", 3), ("shale", 1)]);
+        let theirs = pack(&[("build", 2), ("shale", 5)]);
+        let (merged, conflicts) = Datapack::three_way_merge(&base, &ours, &theirs);
+        assert!(conflicts.is_empty());
+        let expected = pack(&[("build", 3), ("shale", 5)]);
+        assert_eq!(grist_cost(&merged), grist_cost(&expected));
+    }
+
+    #[test]
+    fn same_grist_type_conflicts() {
+        let base = pack(&[("build", 2)]);
+        let ours = pack(&[("build", 3)]);
+        let theirs = pack(&[("build", 4)]);
+        let (merged, conflicts) = Datapack::three_way_merge(&base, &ours, &theirs);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].id, STONE);
+        assert_eq!(conflicts[0].part, ConflictPart::Grist("minestuck:build".to_string()));
+        assert_eq!(conflicts[0].value(MergeSide::Base), Some(&Value::from(2)));
+        assert_eq!(conflicts[0].value(MergeSide::Theirs), Some(&Value::from(4)));
+        //Ours is kept until the conflict is resolved
+        assert_eq!(grist_cost(&merged)["minestuck:build"], 3);
+    }
+
+    #[test]
+    fn one_side_adding_and_other_removing() {
+        let base = pack(&[("build", 2), ("shale", 1)]);
+        let ours = pack(&[("build", 2)]);
+        let theirs = pack(&[("build", 2), ("shale", 1), ("rust", 3)]);
+        let (merged, conflicts) = Datapack::three_way_merge(&base, &ours, &theirs);
+        assert!(conflicts.is_empty());
+        assert_eq!(grist_cost(&merged).len(), 2);
+        assert_eq!(grist_cost(&merged)["minestuck:rust"], 3);
+    }
+}
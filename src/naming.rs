@@ -0,0 +1,237 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{did_you_mean, stable_hash, validate_resource_location, Ingredient, Recipe, ResultItem};
+
+/// Decides the file a recipe is written to, relative to the directory for its recipe type
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum RecipeNaming {
+    /// Named after the grist cost ingredient or the first combination input
+    Item,
+    /// Named after the item a recipe is for, the grist cost ingredient or the combination output
+    #[default]
+    Output,
+    /// Named after a hash of everything that goes into the recipe
+    InputsHash,
+    /// A custom name with `{namespace}`, `{path}`, `{mode}`, `{inputs}` and `{hash}` filled in from the output
+    Template(String),
+}
+
+impl RecipeNaming {
+    /// Full location of the recipe inside a datapack, without the .json extension
+    pub fn location(&self, recipe: &Recipe) -> Option<String> {
+        self.name(recipe)
+            .map(|name| format!("{}/{name}", recipe.directory()))
+    }
+
+    /// Name of the recipe relative to the directory for its type, None if the ids it is named after are invalid
+    ///
+    /// Combinations are named after their inputs inside a directory for the item so that several recipes for one
+    /// output don't end up in the same file
+    pub fn name(&self, recipe: &Recipe) -> Option<String> {
+        let (item, output) = match recipe {
+            Recipe::GristCost(recipe) => (recipe.ingredient.id(), recipe.ingredient.id()),
+            Recipe::Combination(recipe) => {
+                let ResultItem::Item(output) = &recipe.output;
+                (recipe.input1.id(), output.as_str())
+            }
+        };
+        let (namespace, path) = match self {
+            RecipeNaming::Item => item.split_once(':')?,
+            _ => output.split_once(':')?,
+        };
+        match (self, recipe) {
+            (RecipeNaming::Item | RecipeNaming::Output, Recipe::GristCost(recipe)) => match &recipe.ingredient {
+                Ingredient::Nbt(nbt) => Some(format!("{namespace}/{path}.{:08x}", nbt.data_hash())),
+                _ => Some(format!("{namespace}/{path}")),
+            },
+            (RecipeNaming::Item | RecipeNaming::Output, Recipe::Combination(recipe)) => {
+                Some(format!("{namespace}/{path}/{}", recipe.inputs_key()))
+            }
+            (RecipeNaming::InputsHash, _) => Some(format!("{:016x}", inputs_hash(recipe))),
+            (RecipeNaming::Template(template), _) => {
+                let (mode, inputs) = match recipe {
+                    Recipe::GristCost(recipe) => (String::new(), recipe.ingredient.file_name()),
+                    Recipe::Combination(recipe) => (recipe.mode.to_string(), recipe.inputs_key()),
+                };
+                let name = template
+                    .replace("{namespace}", namespace)
+                    .replace("{path}", path)
+                    .replace("{mode}", &mode)
+                    .replace("{inputs}", &inputs)
+                    .replace("{hash}", &format!("{:016x}", inputs_hash(recipe)));
+                //Placeholders that are empty for this recipe, like {mode} for a grist cost, can leave nothing to name
+                //the file
+                if valid_name(&name) {
+                    Some(name)
+                } else {
+                    RecipeNaming::Output.name(recipe)
+                }
+            }
+        }
+    }
+}
+
+/// What can be filled in in a [`RecipeNaming::Template`]
+const PLACEHOLDERS: [&str; 5] = ["namespace", "path", "mode", "inputs", "hash"];
+
+/// An error if a template has placeholders that aren't known or wouldn't make a valid name once they're filled in
+fn check_template(template: &str) -> Result<(), String> {
+    let mut filled = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            return Err(format!("Unclosed {{ in naming template \"{template}\""));
+        };
+        let placeholder = &rest[start + 1..end];
+        if !PLACEHOLDERS.contains(&placeholder) {
+            let suggestion = did_you_mean(placeholder, PLACEHOLDERS)
+                .map(|suggestion| format!(", did you mean {{{suggestion}}}?"))
+                .unwrap_or_else(|| format!(", expected one of {{{}}}", PLACEHOLDERS.join("}, {")));
+            return Err(format!("Unknown placeholder {{{placeholder}}} in naming template \"{template}\"{suggestion}"));
+        }
+        filled.push('x');
+        rest = &rest[end + 1..];
+    }
+    filled.push_str(rest);
+    if valid_name(&filled) {
+        Ok(())
+    } else {
+        Err(format!("Naming template \"{template}\" doesn't make a valid file name"))
+    }
+}
+
+/// Whether a name can be used as a file path inside a datapack, with no empty directory or file names
+fn valid_name(name: &str) -> bool {
+    validate_resource_location(&format!("minestuck:{name}")) && name.split('/').all(|part| !part.is_empty())
+}
+
+fn inputs_hash(recipe: &Recipe) -> u64 {
+    let inputs = match recipe {
+        Recipe::GristCost(recipe) => recipe.ingredient.file_name(),
+        Recipe::Combination(recipe) => recipe.inputs_key(),
+    };
+    stable_hash(&format!("{}\0{inputs}", recipe.directory()))
+}
+
+impl FromStr for RecipeNaming {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "item" => Ok(RecipeNaming::Item),
+            "output" => Ok(RecipeNaming::Output),
+            "inputs_hash" | "hash" => Ok(RecipeNaming::InputsHash),
+            template if template.contains('{') => {
+                check_template(template)?;
+                Ok(RecipeNaming::Template(template.to_string()))
+            }
+            _ => Err(format!("Unknown naming strategy \"{s}\", expected item, output, inputs_hash or a template")),
+        }
+    }
+}
+
+impl Display for RecipeNaming {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecipeNaming::Item => write!(f, "item"),
+            RecipeNaming::Output => write!(f, "output"),
+            RecipeNaming::InputsHash => write!(f, "inputs_hash"),
+            RecipeNaming::Template(template) => write!(f, "{template}"),
+        }
+    }
+}
+
+impl TryFrom<String> for RecipeNaming {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<RecipeNaming> for String {
+    fn from(value: RecipeNaming) -> Self {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CombinationMode, CombinationRecipe, GristCostRecipe};
+
+    fn grist_cost(item: &str) -> Recipe {
+        GristCostRecipe {
+            priority: None,
+            ingredient: Ingredient::Item(item.to_string()),
+            grist_cost: [("minestuck:build".to_string(), 1)].into(),
+            conditions: Default::default(),
+        }
+        .into()
+    }
+
+    fn combination() -> Recipe {
+        Recipe::Combination(CombinationRecipe {
+            input1: Ingredient::Item("minecraft:stone".to_string()),
+            input2: Ingredient::Item("minecraft:stick".to_string()),
+            mode: CombinationMode::And,
+            output: ResultItem::Item("test:spear".to_string()),
+            conditions: Default::default(),
+        })
+    }
+
+    fn naming(s: &str) -> RecipeNaming {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn item_and_output_names() {
+        assert_eq!(naming("item").name(&grist_cost("test:ruby")), Some("test/ruby".to_string()));
+        assert_eq!(naming("output").name(&grist_cost("test:ruby")), Some("test/ruby".to_string()));
+        assert_eq!(
+            naming("item").name(&combination()),
+            Some("minecraft/stone/minecraft.stick_and_minecraft.stone".to_string())
+        );
+        assert_eq!(
+            naming("output").location(&combination()),
+            Some("data/minestuck/recipes/combination/test/spear/minecraft.stick_and_minecraft.stone".to_string())
+        );
+    }
+
+    #[test]
+    fn hash_names() {
+        let name = naming("inputs_hash").name(&grist_cost("test:ruby")).unwrap();
+        assert_eq!(name.len(), 16);
+        assert!(name.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(naming("hash").name(&grist_cost("test:ruby")), Some(name.clone()));
+        assert_ne!(naming("hash").name(&grist_cost("test:sapphire")), Some(name));
+    }
+
+    #[test]
+    fn template_names() {
+        let template = naming("{namespace}/{mode}/{path}");
+        assert_eq!(template.name(&combination()), Some("test/and/spear".to_string()));
+        //{mode} is empty for a grist cost, leaving an empty directory name, so it falls back to output naming
+        assert_eq!(template.name(&grist_cost("test:ruby")), Some("test/ruby".to_string()));
+        assert_eq!(
+            naming("by_input/{inputs}").name(&combination()),
+            Some("by_input/minecraft.stick_and_minecraft.stone".to_string())
+        );
+    }
+
+    #[test]
+    fn bad_templates() {
+        assert_eq!(
+            "{Namespace}/{path}".parse::<RecipeNaming>(),
+            Err("Unknown placeholder {Namespace} in naming template \"{Namespace}/{path}\", did you mean {namespace}?"
+                .to_string())
+        );
+        assert!("{namespace}/{path".parse::<RecipeNaming>().is_err());
+        assert!("{namespace} {path}".parse::<RecipeNaming>().is_err());
+        assert!("outputs".parse::<RecipeNaming>().is_err());
+    }
+}
@@ -0,0 +1,202 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
+
+use crate::{recipe_id, Datapack, GristCostRecipe, Ingredient, ItemTags, Recipe};
+
+/// Priority written by the generator unless another is given, above Minestuck's default for a single item so
+/// these recipes win over built in ones
+pub const GENERATED_PRIORITY: i32 = 101;
+
+/// The priority Minestuck gives a grist cost without one, lower the more items the ingredient matches so recipes
+/// for specific items win over tags
+pub fn default_priority(item_count: usize) -> i32 {
+    100 - (item_count as i32 - 1) * 10
+}
+
+/// Finds which grist cost Minestuck uses for an item out of every pack loaded
+#[derive(Debug, Clone, Default)]
+pub struct CostResolver {
+    tags: ItemTags,
+    /// By location, which packs loaded later replace
+    recipes: BTreeMap<String, ResolverEntry>,
+    /// Locations of the recipes matching each item
+    by_item: BTreeMap<String, BTreeSet<String>>,
+}
+
+#[derive(Debug, Clone)]
+struct ResolverEntry {
+    pack: String,
+    recipe: GristCostRecipe,
+    items: BTreeSet<String>,
+}
+
+/// A grist cost recipe that matches an item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CostCandidate<'a> {
+    /// Name of the pack the recipe comes from
+    pub pack: &'a str,
+    pub location: &'a str,
+    pub recipe: &'a GristCostRecipe,
+    /// The recipe's priority, or Minestuck's default for its ingredient if it doesn't have one
+    pub priority: i32,
+    /// How many items the ingredient matches
+    pub item_count: usize,
+}
+
+/// Every grist cost recipe for an item, highest priority first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CostResolution<'a> {
+    pub item: String,
+    pub candidates: Vec<CostCandidate<'a>>,
+}
+
+impl CostResolver {
+    /// Tags are needed to know which items tag ingredients match
+    pub fn new(tags: ItemTags) -> CostResolver {
+        CostResolver {
+            tags,
+            ..Default::default()
+        }
+    }
+
+    /// Adds a pack on top of the ones added before, replacing recipes at the same location and removing ones its
+    /// filter hides
+    pub fn add_pack(&mut self, name: &str, datapack: &Datapack) {
+        let hidden: Vec<String> = self
+            .recipes
+            .keys()
+            .filter(|location| recipe_id(location).is_some_and(|id| datapack.mcmeta.hides_recipe(&id)))
+            .cloned()
+            .collect();
+        for location in hidden {
+            self.remove(&location);
+        }
+        for (location, recipe) in &datapack.recipes {
+            if let Recipe::GristCost(recipe) = recipe {
+                self.add(name, location, recipe);
+            }
+        }
+    }
+
+    /// Adds a single recipe, replacing any at the same location
+    ///
+    /// Recipes with tags that can't be resolved are left out, since Minestuck can't load them either
+    pub fn add(&mut self, pack: &str, location: &str, recipe: &GristCostRecipe) {
+        self.remove(location);
+        if let Ok(items) = self.tags.ingredient_items(&recipe.ingredient) {
+            for item in &items {
+                self.by_item.entry(item.clone()).or_default().insert(location.to_string());
+            }
+            let entry = ResolverEntry {
+                pack: pack.to_string(),
+                recipe: recipe.clone(),
+                items,
+            };
+            self.recipes.insert(location.to_string(), entry);
+        }
+    }
+
+    /// Removes the recipe at a location, returning whether there was one
+    pub fn remove(&mut self, location: &str) -> bool {
+        let Some(entry) = self.recipes.remove(location) else {
+            return false;
+        };
+        for item in &entry.items {
+            if let Some(locations) = self.by_item.get_mut(item) {
+                locations.remove(location);
+            }
+        }
+        true
+    }
+
+    pub fn tags(&self) -> &ItemTags {
+        &self.tags
+    }
+
+    /// Every recipe that matches the item, highest priority first
+    ///
+    /// Recipes that need NBT are only included for the item with that NBT, written as an ingredient
+    pub fn resolve(&self, item: &Ingredient) -> CostResolution<'_> {
+        let id = item.id();
+        let mut candidates: Vec<CostCandidate> = self
+            .by_item
+            .get(id)
+            .into_iter()
+            .flatten()
+            .filter_map(|location| self.recipes.get_key_value(location))
+            .filter(|(_, entry)| match &entry.recipe.ingredient {
+                Ingredient::Nbt(_) => entry.recipe.ingredient == *item,
+                _ => true,
+            })
+            .map(|(location, entry)| CostCandidate {
+                pack: &entry.pack,
+                location,
+                recipe: &entry.recipe,
+                priority: entry.recipe.priority.unwrap_or_else(|| default_priority(entry.items.len())),
+                item_count: entry.items.len(),
+            })
+            .collect();
+        //Stable, so ties stay in location order
+        candidates.sort_by_key(|candidate| -candidate.priority);
+        CostResolution {
+            item: item.to_string(),
+            candidates,
+        }
+    }
+}
+
+impl<'a> CostResolution<'a> {
+    /// The recipe Minestuck uses, if any match
+    pub fn effective(&self) -> Option<&CostCandidate<'a>> {
+        self.candidates.first()
+    }
+
+    /// Other recipes with the same priority as the effective one, where which one Minestuck uses isn't defined
+    pub fn tied(&self) -> &[CostCandidate<'a>] {
+        match self.candidates.first() {
+            Some(first) => {
+                let count = self.candidates[1..]
+                    .iter()
+                    .take_while(|candidate| candidate.priority == first.priority)
+                    .count();
+                &self.candidates[1..=count]
+            }
+            None => &[],
+        }
+    }
+}
+
+impl Display for CostCandidate<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cost: Vec<String> = self
+            .recipe
+            .grist_cost
+            .iter()
+            .map(|(grist, amount)| format!("{}={amount}", grist.strip_prefix("minestuck:").unwrap_or(grist)))
+            .collect();
+        write!(f, "{} from {} in {}, priority {}", cost.join(","), self.location, self.pack, self.priority)?;
+        if self.recipe.priority.is_none() {
+            let items = if self.item_count == 1 { "item" } else { "items" };
+            write!(f, " (default for {} {items})", self.item_count)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for CostResolution<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some(effective) = self.effective() else {
+            return write!(f, "{} has no grist cost", self.item);
+        };
+        write!(f, "{} costs {effective}", self.item)?;
+        for tied in self.tied() {
+            write!(f, "\n  tied with {tied}, Minestuck may use either")?;
+        }
+        for other in &self.candidates[1 + self.tied().len()..] {
+            write!(f, "\n  overrides {other}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,289 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    str::FromStr,
+};
+
+use crate::{grist_resource, validate_resource_location, Datapack, Ingredient, ItemTags, Recipe, ResultItem};
+
+/// Lookups on a datapack's recipes by output, input, grist cost and namespace, built once so queries don't have
+/// to go through every recipe
+///
+/// The recipes are either borrowed or, with [`RecipeIndex::owned`], kept in the index so it can be stored
+#[derive(Debug, Clone)]
+pub struct RecipeIndex<'a> {
+    recipes: Cow<'a, BTreeMap<String, Recipe>>,
+    /// Items a grist cost is for or a combination makes, and tags as `#modid:tag`
+    by_output: BTreeMap<String, BTreeSet<String>>,
+    /// Items and tags either input of a combination takes
+    by_input: BTreeMap<String, BTreeSet<String>>,
+    /// Amount and location of each grist cost using a grist type, sorted by amount
+    by_grist: BTreeMap<String, Vec<(i32, String)>>,
+    by_namespace: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Recipes to find, written as terms separated by spaces
+///
+/// - `output=modid:item` recipes for an item, grist costs for it and combinations making it
+/// - `input=modid:item` combinations that take an item in either slot
+/// - `namespace=modid` recipes for items from a mod
+/// - `build>10`, `build<=10`, `build=10` or `build=5..10` grist costs with that much of a grist type
+/// - anything else, including terms whose part before the `=` isn't a grist type id, has to be part of the recipe's
+///   location or csv form
+///
+/// Every term has to match
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecipeQuery {
+    pub output: Option<String>,
+    pub input: Option<String>,
+    pub namespace: Option<String>,
+    /// Grist type and the smallest and largest amount allowed
+    pub grist: Vec<(String, i32, i32)>,
+    pub text: Vec<String>,
+}
+
+fn namespace(id: &str) -> Option<&str> {
+    id.split_once(':').map(|(namespace, _)| namespace)
+}
+
+/// Keys an ingredient is indexed under, its items and tags and the items in those tags if they're known
+fn ingredient_keys(ingredient: &Ingredient, tags: Option<&ItemTags>) -> BTreeSet<String> {
+    let mut keys: BTreeSet<String> = ingredient.item_ids().into_iter().map(str::to_string).collect();
+    keys.extend(ingredient.tag_ids().into_iter().map(|tag| format!("#{tag}")));
+    if let Some(items) = tags.and_then(|tags| tags.ingredient_items(ingredient).ok()) {
+        keys.extend(items);
+    }
+    keys
+}
+
+impl Datapack {
+    /// Indexes the recipes, which have to stay the same while the index is used
+    pub fn index(&self) -> RecipeIndex<'_> {
+        RecipeIndex::new(&self.recipes, None)
+    }
+
+    /// Indexes the recipes, with tag ingredients also found by the items in the tags
+    pub fn index_with_tags<'a>(&'a self, tags: &ItemTags) -> RecipeIndex<'a> {
+        RecipeIndex::new(&self.recipes, Some(tags))
+    }
+}
+
+impl<'a> RecipeIndex<'a> {
+    pub fn new(recipes: &'a BTreeMap<String, Recipe>, tags: Option<&ItemTags>) -> RecipeIndex<'a> {
+        Self::build(Cow::Borrowed(recipes), tags)
+    }
+
+    /// Indexes recipes that aren't kept anywhere else, such as ones made from rows in the GUI
+    pub fn owned(recipes: BTreeMap<String, Recipe>, tags: Option<&ItemTags>) -> RecipeIndex<'static> {
+        RecipeIndex::build(Cow::Owned(recipes), tags)
+    }
+
+    fn build(recipes: Cow<'a, BTreeMap<String, Recipe>>, tags: Option<&ItemTags>) -> RecipeIndex<'a> {
+        let mut index = RecipeIndex {
+            recipes,
+            by_output: BTreeMap::new(),
+            by_input: BTreeMap::new(),
+            by_grist: BTreeMap::new(),
+            by_namespace: BTreeMap::new(),
+        };
+        for (location, recipe) in index.recipes.iter() {
+            match recipe {
+                Recipe::GristCost(recipe) => {
+                    for key in ingredient_keys(&recipe.ingredient, tags) {
+                        index.by_output.entry(key).or_default().insert(location.clone());
+                    }
+                    for (grist, amount) in &recipe.grist_cost {
+                        index.by_grist.entry(grist.clone()).or_default().push((*amount, location.clone()));
+                    }
+                    if let Some(namespace) = namespace(recipe.ingredient.id()) {
+                        index.by_namespace.entry(namespace.to_string()).or_default().insert(location.clone());
+                    }
+                }
+                Recipe::Combination(recipe) => {
+                    let ResultItem::Item(output) = &recipe.output;
+                    index.by_output.entry(output.clone()).or_default().insert(location.clone());
+                    let mut inputs = ingredient_keys(&recipe.input1, tags);
+                    inputs.extend(ingredient_keys(&recipe.input2, tags));
+                    for key in inputs {
+                        index.by_input.entry(key).or_default().insert(location.clone());
+                    }
+                    if let Some(namespace) = namespace(output) {
+                        index.by_namespace.entry(namespace.to_string()).or_default().insert(location.clone());
+                    }
+                }
+            }
+        }
+        for amounts in index.by_grist.values_mut() {
+            amounts.sort();
+        }
+        index
+    }
+
+    fn recipes<'b>(&self, locations: impl IntoIterator<Item = &'b str>) -> Vec<(&str, &Recipe)> {
+        locations
+            .into_iter()
+            .filter_map(|location| self.recipes.get_key_value(location))
+            .map(|(location, recipe)| (location.as_str(), recipe))
+            .collect()
+    }
+
+    fn lookup<'b>(index: &'b BTreeMap<String, BTreeSet<String>>, key: &str) -> BTreeSet<&'b str> {
+        index.get(key).into_iter().flatten().map(String::as_str).collect()
+    }
+
+    /// Grist costs for an item or `#modid:tag`, and combinations that make an item
+    pub fn by_output(&self, item: &str) -> Vec<(&str, &Recipe)> {
+        self.recipes(Self::lookup(&self.by_output, item))
+    }
+
+    /// Combinations that take an item or `#modid:tag` in either slot
+    pub fn by_input(&self, item: &str) -> Vec<(&str, &Recipe)> {
+        self.recipes(Self::lookup(&self.by_input, item))
+    }
+
+    /// Grist costs with between min and max of a grist type, inclusive. `minestuck:` is added if there's no namespace
+    pub fn by_grist(&self, grist: &str, min: i32, max: i32) -> Vec<(&str, &Recipe)> {
+        self.recipes(self.grist_locations(grist, min, max))
+    }
+
+    fn grist_locations(&self, grist: &str, min: i32, max: i32) -> BTreeSet<&str> {
+        let Some(amounts) = self.by_grist.get(grist_resource(grist).as_str()) else {
+            return BTreeSet::new();
+        };
+        let start = amounts.partition_point(|(amount, _)| *amount < min);
+        let end = amounts.partition_point(|(amount, _)| *amount <= max);
+        amounts[start..end.max(start)].iter().map(|(_, location)| location.as_str()).collect()
+    }
+
+    /// Recipes for items from a mod
+    pub fn by_namespace(&self, namespace: &str) -> Vec<(&str, &Recipe)> {
+        self.recipes(Self::lookup(&self.by_namespace, namespace))
+    }
+
+    /// Recipes matching every term in the query, in location order
+    pub fn query<'b>(&'b self, query: &RecipeQuery) -> Vec<(&'b str, &'b Recipe)> {
+        //Each indexed term narrows down the locations, and if there are none every recipe is checked
+        let mut locations: Option<BTreeSet<&'b str>> = None;
+        let mut narrow = |found: BTreeSet<&'b str>| {
+            locations = Some(match locations.take() {
+                Some(locations) => locations.intersection(&found).copied().collect(),
+                None => found,
+            });
+        };
+        if let Some(output) = &query.output {
+            narrow(Self::lookup(&self.by_output, output));
+        }
+        if let Some(input) = &query.input {
+            narrow(Self::lookup(&self.by_input, input));
+        }
+        if let Some(namespace) = &query.namespace {
+            narrow(Self::lookup(&self.by_namespace, namespace));
+        }
+        for (grist, min, max) in &query.grist {
+            narrow(self.grist_locations(grist, *min, *max));
+        }
+        let locations = locations.unwrap_or_else(|| self.recipes.keys().map(String::as_str).collect());
+        self.recipes(locations)
+            .into_iter()
+            .filter(|(location, recipe)| {
+                query.text.is_empty() || {
+                    let csv = recipe.to_string();
+                    query.text.iter().all(|text| location.contains(text.as_str()) || csv.contains(text.as_str()))
+                }
+            })
+            .collect()
+    }
+}
+
+impl RecipeQuery {
+    /// Whether nothing would be filtered out
+    pub fn is_empty(&self) -> bool {
+        *self == RecipeQuery::default()
+    }
+}
+
+impl FromStr for RecipeQuery {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut query = RecipeQuery::default();
+        for term in s.split_whitespace() {
+            let Some(split) = term.find(['=', '<', '>']) else {
+                query.text.push(term.to_string());
+                continue;
+            };
+            let (key, rest) = term.split_at(split);
+            match key {
+                "output" | "input" | "namespace" => {
+                    let Some(value) = rest.strip_prefix('=') else {
+                        return Err(format!("{key} can only be compared with =, in \"{term}\""));
+                    };
+                    let field = match key {
+                        "output" => &mut query.output,
+                        "input" => &mut query.input,
+                        _ => &mut query.namespace,
+                    };
+                    *field = Some(value.to_string());
+                }
+                //Csv forms of recipes have = in them too
+                _ if key.is_empty() || !validate_resource_location(&grist_resource(key)) => {
+                    query.text.push(term.to_string())
+                }
+                _ => {
+                    let parse = |amount: &str| {
+                        amount
+                            .parse::<i32>()
+                            .map_err(|e| format!("{e} in \"{term}\""))
+                    };
+                    let (min, max) = if let Some(amount) = rest.strip_prefix(">=") {
+                        (parse(amount)?, i32::MAX)
+                    } else if let Some(amount) = rest.strip_prefix("<=") {
+                        (i32::MIN, parse(amount)?)
+                    } else if let Some(amount) = rest.strip_prefix('>') {
+                        (parse(amount)?.saturating_add(1), i32::MAX)
+                    } else if let Some(amount) = rest.strip_prefix('<') {
+                        (i32::MIN, parse(amount)?.saturating_sub(1))
+                    } else {
+                        let amount = &rest[1..];
+                        match amount.split_once("..") {
+                            Some((min, max)) => (parse(min)?, parse(max)?),
+                            None => (parse(amount)?, parse(amount)?),
+                        }
+                    };
+                    query.grist.push((grist_resource(key), min, max));
+                }
+            }
+        }
+        Ok(query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn terms() {
+        let query: RecipeQuery = "output=minecraft:stone build>=2 shale=1..3 cobble".parse().unwrap();
+        assert_eq!(query.output.as_deref(), Some("minecraft:stone"));
+        assert_eq!(
+            query.grist,
+            [("minestuck:build".to_string(), 2, i32::MAX), ("minestuck:shale".to_string(), 1, 3)]
+        );
+        assert_eq!(query.text, ["cobble"]);
+    }
+
+    #[test]
+    fn only_equal_for_output_input_and_namespace() {
+        assert!("output>5".parse::<RecipeQuery>().is_err());
+        assert!("input<minecraft:stone".parse::<RecipeQuery>().is_err());
+        assert!("namespace>=minecraft".parse::<RecipeQuery>().is_err());
+    }
+
+    #[test]
+    fn csv_text_isnt_a_grist_filter() {
+        let query: RecipeQuery = "stone,build=2".parse().unwrap();
+        assert!(query.grist.is_empty());
+        assert_eq!(query.text, ["stone,build=2"]);
+    }
+}
@@ -0,0 +1,178 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
+
+use crate::{
+    estimate_costs, recipe_cost, AlchemyGraph, CostResolver, CraftingRecipe, DraftCost, EstimateFormula, Ingredient,
+};
+
+/// Grist types and amounts, added up across several recipes
+pub type GristTotals = BTreeMap<String, i64>;
+
+/// Total amount of every grist type, used to pick the cheapest of several costs
+pub fn grist_total(grist: &GristTotals) -> i64 {
+    grist.values().sum()
+}
+
+/// Adds the second set of grist to the first
+pub fn add_grist(totals: &mut GristTotals, grist: &GristTotals) {
+    for (grist, amount) in grist {
+        *totals.entry(grist.clone()).or_default() += amount;
+    }
+}
+
+/// The explicit and derived cost of an item made by combination
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CostComparison {
+    pub item: String,
+    /// From a grist cost recipe, and the location of that recipe
+    pub explicit: Option<(GristTotals, String)>,
+    /// What Minestuck would generate from the cheapest recipe making the item
+    pub derived: Option<DraftCost>,
+    /// Whether any crafting, smelting or stonecutting recipe makes the item
+    pub crafted: bool,
+}
+
+/// Costs of every combination output, both explicit and as Minestuck derives them
+///
+/// Minestuck only generates costs from crafting, smelting and stonecutting recipes, never from combinations, so
+/// derived costs use the [`EstimateFormula`] on those recipes. Outputs only made by combination and without a
+/// grist cost can't be costed at all
+#[derive(Debug, Clone, Default)]
+pub struct CostRollup {
+    explicit: BTreeMap<String, (GristTotals, String)>,
+    derived: BTreeMap<String, DraftCost>,
+    /// Outputs made by a crafting recipe, whether or not its inputs have costs
+    crafted: BTreeSet<String>,
+}
+
+impl CostRollup {
+    /// The cost the player pays, the explicit one if the item has one
+    pub fn cost(&self, item: &str) -> Option<&GristTotals> {
+        self.explicit
+            .get(item)
+            .map(|(grist, _)| grist)
+            .or_else(|| self.derived.get(item).map(|derived| &derived.grist))
+    }
+
+    pub fn explicit(&self, item: &str) -> Option<&(GristTotals, String)> {
+        self.explicit.get(item)
+    }
+
+    pub fn derived(&self, item: &str) -> Option<&DraftCost> {
+        self.derived.get(item)
+    }
+
+    /// Whether an item's only cost is derived from a crafting recipe
+    pub fn is_derived(&self, item: &str) -> bool {
+        !self.explicit.contains_key(item) && self.derived.contains_key(item)
+    }
+
+    /// The explicit and derived cost of every output of a combination
+    pub fn comparisons(&self, graph: &AlchemyGraph) -> Vec<CostComparison> {
+        let mut outputs: Vec<&str> = graph.edges().map(|edge| edge.output.as_str()).collect();
+        outputs.sort();
+        outputs.dedup();
+        outputs
+            .into_iter()
+            .map(|item| CostComparison {
+                item: item.to_string(),
+                explicit: self.explicit.get(item).cloned(),
+                derived: self.derived.get(item).cloned(),
+                crafted: self.crafted.contains(item),
+            })
+            .collect()
+    }
+}
+
+impl CostResolver {
+    /// The grist cost Minestuck uses for an item and the location of its recipe
+    pub fn item_cost(&self, item: &str) -> Option<(GristTotals, String)> {
+        let resolution = self.resolve(&Ingredient::Item(item.to_string()));
+        let effective = resolution.effective()?;
+        let grist = effective
+            .recipe
+            .grist_cost
+            .iter()
+            .map(|(grist, amount)| (grist.clone(), *amount as i64))
+            .collect();
+        Some((grist, effective.location.to_string()))
+    }
+}
+
+impl AlchemyGraph {
+    /// Works out the explicit and derived cost of every combination output, see [`CostRollup`]
+    pub fn roll_up_costs(&self, crafting: &[CraftingRecipe], formula: &EstimateFormula) -> CostRollup {
+        let mut rollup = CostRollup::default();
+        let resolver = self.resolver();
+        //Inputs without a grist cost are priced the way Minestuck would generate one
+        let drafts: BTreeMap<String, GristTotals> = estimate_costs(resolver, crafting, formula)
+            .into_iter()
+            .map(|draft| (draft.item, draft.grist))
+            .collect();
+        let mut known: BTreeMap<String, Option<GristTotals>> = BTreeMap::new();
+        let mut cost = |item: &str| -> Option<GristTotals> {
+            known
+                .entry(item.to_string())
+                .or_insert_with(|| {
+                    let explicit = resolver.item_cost(item).map(|(grist, _)| grist);
+                    explicit.or_else(|| drafts.get(item).cloned())
+                })
+                .clone()
+        };
+        let outputs: BTreeSet<&str> = self.edges().map(|edge| edge.output.as_str()).collect();
+        for &item in &outputs {
+            if let Some(explicit) = resolver.item_cost(item) {
+                rollup.explicit.insert(item.to_string(), explicit);
+            }
+        }
+        for recipe in crafting.iter().filter(|recipe| outputs.contains(recipe.output.as_str())) {
+            rollup.crafted.insert(recipe.output.clone());
+            let Some(grist) = recipe_cost(recipe, formula, resolver.tags(), &mut cost) else {
+                continue;
+            };
+            let cheaper = rollup
+                .derived
+                .get(&recipe.output)
+                .is_none_or(|derived| grist_total(&grist) < grist_total(&derived.grist));
+            if cheaper {
+                let derived = DraftCost {
+                    item: recipe.output.clone(),
+                    grist,
+                    source: recipe.location.clone(),
+                    kind: recipe.kind,
+                };
+                rollup.derived.insert(recipe.output.clone(), derived);
+            }
+        }
+        rollup
+    }
+}
+
+/// Writes grist the way `grist_json` reads it, `build=2,ruby=1`
+pub fn format_grist(grist: &GristTotals) -> String {
+    grist
+        .iter()
+        .map(|(grist, amount)| format!("{}={amount}", grist.strip_prefix("minestuck:").unwrap_or(grist)))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+impl Display for CostComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.item)?;
+        match &self.explicit {
+            Some((grist, location)) => write!(f, " costs {} from {location}", format_grist(grist))?,
+            None => write!(f, " has no grist cost")?,
+        }
+        match &self.derived {
+            Some(derived) => {
+                write!(f, ", Minestuck would derive {} from {}", format_grist(&derived.grist), derived.source)
+            }
+            None if self.crafted => write!(f, ", its crafting recipes use items with no known cost"),
+            None if self.explicit.is_some() => write!(f, ", no crafting recipe to derive a cost from"),
+            None => write!(f, ", only made by combination so it can't be costed"),
+        }
+    }
+}
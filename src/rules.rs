@@ -0,0 +1,334 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    path::Path,
+};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    grist_resource, validate_resource_location, GristCostRecipe, Ingredient, ItemTags, Recipe, GENERATED_PRIORITY,
+};
+
+/// Gives every item it matches the same grist cost, from a rules file
+///
+/// Every matcher given has to match. In grist types, `{namespace}` and `{path}` are replaced with the parts of the
+/// item id and `{1}`, `{2}`... with what each `*` and `?` in the glob matched, then what the regex's groups matched
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CostRule {
+    /// Shown in the preview, the rule's position in the file if there isn't one
+    pub name: Option<String>,
+    /// Matches whole item ids, `*` matching anything and `?` any one character
+    pub glob: Option<String>,
+    /// Matches whole item ids
+    pub regex: Option<String>,
+    /// Items in this tag, without the `#`
+    pub tag: Option<String>,
+    pub grist_cost: BTreeMap<String, i32>,
+    /// Defaults to [`GENERATED_PRIORITY`]
+    pub priority: Option<i32>,
+}
+
+/// The recipes a rule made, and the items it matched but didn't make one for
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleOutput {
+    pub name: String,
+    pub recipes: Vec<GristCostRecipe>,
+    /// Items with their own grist cost recipe
+    pub explicit: Vec<String>,
+    /// Items an earlier rule already made a recipe for
+    pub earlier: Vec<String>,
+    /// Items the rule matched but couldn't make a recipe for
+    pub problems: Vec<String>,
+}
+
+/// A rule with its patterns compiled, the glob turned into a regex
+struct Matcher<'a> {
+    rule: &'a CostRule,
+    glob: Option<Regex>,
+    regex: Option<Regex>,
+    tag_items: Option<BTreeSet<String>>,
+}
+
+fn glob_regex(glob: &str) -> Result<Regex, regex::Error> {
+    let mut pattern = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => pattern.push_str("(.*)"),
+            '?' => pattern.push_str("(.)"),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+    Regex::new(&pattern)
+}
+
+impl Matcher<'_> {
+    /// What the groups matched if the item matches the rule
+    fn captures(&self, item: &str) -> Option<Vec<String>> {
+        if self.tag_items.as_ref().is_some_and(|items| !items.contains(item)) {
+            return None;
+        }
+        let mut captures = vec![];
+        for regex in [&self.glob, &self.regex].into_iter().flatten() {
+            let found = regex.captures(item)?;
+            captures.extend(
+                found
+                    .iter()
+                    .skip(1)
+                    .map(|group| group.map(|group| group.as_str().to_string()).unwrap_or_default()),
+            );
+        }
+        Some(captures)
+    }
+
+    /// The grist cost for an item, an error if filling in the grist types doesn't give valid ids
+    fn recipe(&self, item: &str, captures: &[String]) -> Result<GristCostRecipe, String> {
+        let (namespace, path) = item.split_once(':').unwrap_or(("minecraft", item));
+        let grist_cost = self
+            .rule
+            .grist_cost
+            .iter()
+            .map(|(template, amount)| {
+                let mut grist = template.replace("{namespace}", namespace).replace("{path}", path);
+                for (i, capture) in captures.iter().enumerate() {
+                    grist = grist.replace(&format!("{{{}}}", i + 1), capture);
+                }
+                if grist.contains('{') {
+                    let groups = plural(captures.len(), "group");
+                    return Err(format!("{item}: {template} has a placeholder that isn't filled in, with {groups}"));
+                }
+                let grist = grist_resource(&grist);
+                if !validate_resource_location(&grist) {
+                    return Err(format!("{item}: {template} gives {grist}, which isn't a valid grist type id"));
+                }
+                Ok((grist, *amount))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(GristCostRecipe {
+            priority: Some(self.rule.priority.unwrap_or(GENERATED_PRIORITY)),
+            ingredient: Ingredient::Item(item.to_string()),
+            grist_cost,
+            conditions: Default::default(),
+        })
+    }
+}
+
+/// Reads a rules file, a json list of [`CostRule`]s
+pub fn load_cost_rules<P>(path: P) -> Result<Vec<CostRule>, String>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let json = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {path:?}, {e}"))?;
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse {path:?}, {e}"))
+}
+
+/// Makes grist costs for the items each rule matches, the first rule matching an item winning
+///
+/// Items with an item grist cost in `recipes` are left alone, so explicit recipes take precedence over rules
+pub fn apply_cost_rules<'a>(
+    rules: &[CostRule],
+    items: impl IntoIterator<Item = &'a str>,
+    tags: &ItemTags,
+    recipes: impl IntoIterator<Item = &'a Recipe>,
+) -> Result<Vec<RuleOutput>, String> {
+    let matchers = rules
+        .iter()
+        .enumerate()
+        .map(|(i, rule)| {
+            let name = rule.name.clone().unwrap_or_else(|| format!("rule {}", i + 1));
+            if rule.glob.is_none() && rule.regex.is_none() && rule.tag.is_none() {
+                return Err(format!("{name} has no glob, regex or tag to match items with"));
+            }
+            let glob = rule
+                .glob
+                .as_deref()
+                .map(glob_regex)
+                .transpose()
+                .map_err(|e| format!("Bad glob in {name}, {e}"))?;
+            let regex = rule
+                .regex
+                .as_deref()
+                .map(|regex| Regex::new(&format!("^(?:{regex})$")))
+                .transpose()
+                .map_err(|e| format!("Bad regex in {name}, {e}"))?;
+            let tag_items = rule
+                .tag
+                .as_deref()
+                .map(|tag| tags.resolve(tag.trim_start_matches('#')))
+                .transpose()
+                .map_err(|e| format!("Bad tag in {name}, {e}"))?;
+            let matcher = Matcher {
+                rule,
+                glob,
+                regex,
+                tag_items,
+            };
+            Ok((name, matcher))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let explicit: BTreeSet<&str> = recipes
+        .into_iter()
+        .filter_map(|recipe| match recipe {
+            Recipe::GristCost(GristCostRecipe {
+                ingredient: Ingredient::Item(item),
+                ..
+            }) => Some(item.as_str()),
+            _ => None,
+        })
+        .collect();
+    let items: BTreeSet<&str> = items.into_iter().collect();
+
+    let mut outputs: Vec<RuleOutput> = matchers
+        .iter()
+        .map(|(name, _)| RuleOutput {
+            name: name.clone(),
+            ..Default::default()
+        })
+        .collect();
+    for item in items {
+        let mut taken = false;
+        for ((_, matcher), output) in matchers.iter().zip(&mut outputs) {
+            let Some(captures) = matcher.captures(item) else {
+                continue;
+            };
+            if explicit.contains(item) {
+                output.explicit.push(item.to_string());
+            } else if taken {
+                output.earlier.push(item.to_string());
+            } else {
+                match matcher.recipe(item, &captures) {
+                    Ok(recipe) => output.recipes.push(recipe),
+                    Err(e) => output.problems.push(e),
+                }
+                taken = true;
+            }
+        }
+    }
+    Ok(outputs)
+}
+
+impl RuleOutput {
+    /// Every recipe the rule made and every item it skipped, for checking a rule does what it's meant to
+    pub fn preview(&self) -> String {
+        let mut preview = format!("{}: {}\n", self.name, plural(self.recipes.len(), "recipe"));
+        for recipe in &self.recipes {
+            preview.push_str(&format!("  {}\n", Recipe::GristCost(recipe.clone())));
+        }
+        for item in &self.explicit {
+            preview.push_str(&format!("  {item} skipped, it has its own recipe\n"));
+        }
+        for item in &self.earlier {
+            preview.push_str(&format!("  {item} skipped, an earlier rule matched it\n"));
+        }
+        for problem in &self.problems {
+            preview.push_str(&format!("  {problem}\n"));
+        }
+        preview
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+impl Display for RuleOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {}, {} with their own recipe, {} matched by an earlier rule",
+            self.name,
+            plural(self.recipes.len(), "recipe"),
+            plural(self.explicit.len(), "item"),
+            plural(self.earlier.len(), "item")
+        )?;
+        if !self.problems.is_empty() {
+            write!(f, ", {}", plural(self.problems.len(), "problem"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(glob: Option<&str>, regex: Option<&str>, grist: &str) -> CostRule {
+        CostRule {
+            glob: glob.map(str::to_string),
+            regex: regex.map(str::to_string),
+            grist_cost: BTreeMap::from([(grist.to_string(), 1)]),
+            ..Default::default()
+        }
+    }
+
+    fn apply(rules: &[CostRule], items: &[&str], recipes: &[Recipe]) -> Vec<RuleOutput> {
+        apply_cost_rules(rules, items.iter().copied(), &ItemTags::new(), recipes).unwrap()
+    }
+
+    fn grist(recipe: &GristCostRecipe) -> Vec<&str> {
+        recipe.grist_cost.keys().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn globs() {
+        let regex = glob_regex("test:*_ore?").unwrap();
+        assert_eq!(regex.as_str(), "^test:(.*)_ore(.)$");
+        assert!(regex.is_match("test:ruby_ores"));
+        assert!(!regex.is_match("test:ruby_ore"));
+        assert!(!regex.is_match("othertest:ruby_ores"));
+        assert!(glob_regex("test:a.b").unwrap().is_match("test:a.b"));
+        assert!(!glob_regex("test:a.b").unwrap().is_match("test:axb"));
+    }
+
+    #[test]
+    fn captures() {
+        let rules = [
+            rule(Some("test:*_ore"), None, "{namespace}:{1}"),
+            rule(Some("test:*_block"), Some("test:(\\w+)_(\\w+)"), "{path}_{1}_{3}"),
+        ];
+        let outputs = apply(&rules, &["test:ruby_ore", "test:ruby_block"], &[]);
+        assert_eq!(grist(&outputs[0].recipes[0]), ["test:ruby"]);
+        //Glob wildcards come before regex groups
+        assert_eq!(grist(&outputs[1].recipes[0]), ["minestuck:ruby_block_ruby_block"]);
+    }
+
+    #[test]
+    fn placeholders_left_over() {
+        let outputs = apply(&[rule(Some("test:*_ore"), None, "test:{2}")], &["test:ruby_ore"], &[]);
+        assert!(outputs[0].recipes.is_empty());
+        assert_eq!(
+            outputs[0].problems,
+            ["test:ruby_ore: test:{2} has a placeholder that isn't filled in, with 1 group"]
+        );
+        let outputs = apply(&[rule(Some("test:*"), None, "test:{1}!")], &["test:ruby"], &[]);
+        assert_eq!(outputs[0].problems, ["test:ruby: test:{1}! gives test:ruby!, which isn't a valid grist type id"]);
+    }
+
+    #[test]
+    fn first_rule_wins_and_explicit_costs_are_skipped() {
+        let rules = [rule(Some("test:ruby*"), None, "ruby"), rule(Some("test:*"), None, "build")];
+        let explicit: Recipe = GristCostRecipe {
+            priority: None,
+            ingredient: Ingredient::Item("test:ruby_gem".to_string()),
+            grist_cost: BTreeMap::from([("minestuck:ruby".to_string(), 2)]),
+            conditions: Default::default(),
+        }
+        .into();
+        let outputs = apply(&rules, &["test:ruby", "test:ruby_gem", "test:stone"], &[explicit]);
+        assert_eq!(outputs[0].recipes.len(), 1);
+        assert_eq!(outputs[0].recipes[0].ingredient, Ingredient::Item("test:ruby".to_string()));
+        assert_eq!(outputs[0].explicit, ["test:ruby_gem"]);
+        assert_eq!(outputs[1].recipes.len(), 1);
+        assert_eq!(outputs[1].recipes[0].ingredient, Ingredient::Item("test:stone".to_string()));
+        assert_eq!(outputs[1].earlier, ["test:ruby"]);
+        assert_eq!(outputs[1].explicit, ["test:ruby_gem"]);
+    }
+}
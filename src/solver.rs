@@ -0,0 +1,408 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    str::FromStr,
+};
+
+use crate::{
+    expression::is_whole, format_grist, recipe_amounts, GristCostRecipe, GristExpression, GristTotals, Ingredient,
+    GENERATED_PRIORITY,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relation {
+    Equal,
+    AtLeast,
+    AtMost,
+}
+
+/// A rule an item's cost has to follow, such as `minecraft:iron_block = 9 * minecraft:iron_ingot`
+///
+/// Costs are compared for each grist type, so `>=` means at least as much of every type. An item equal to only
+/// grist, such as `minecraft:iron_ingot = build:2 + rust:6`, pins its cost
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostConstraint {
+    pub item: String,
+    pub relation: Relation,
+    pub expression: GristExpression,
+}
+
+/// A constraint that couldn't be met, and why
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstraintProblem {
+    pub constraint: CostConstraint,
+    pub reason: String,
+}
+
+/// Costs worked out from constraints, and the constraints that couldn't be met
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CostSolution {
+    /// Every item that could be given a cost, apart from ones that already had one
+    pub costs: BTreeMap<String, GristTotals>,
+    pub problems: Vec<ConstraintProblem>,
+    /// Items on the left of a constraint that couldn't be given a cost
+    pub unsolved: Vec<String>,
+}
+
+/// The expression's cost, None while an item in it has no cost
+fn evaluate(
+    expression: &GristExpression,
+    cost: impl Fn(&str) -> Option<GristTotals>,
+) -> Option<Result<GristTotals, String>> {
+    let costs: BTreeMap<&str, GristTotals> = expression
+        .items()
+        .into_iter()
+        .map(|item| Some((item, cost(item)?)))
+        .collect::<Option<_>>()?;
+    Some(expression.evaluate(None, &|item| costs.get(item).cloned()))
+}
+
+/// The amounts for a grist cost recipe, an error if a cost can't be written in one
+pub(crate) fn check_amounts(item: &str, cost: &GristTotals) -> Result<BTreeMap<String, i32>, String> {
+    recipe_amounts(cost).map_err(|e| format!("{item} would cost {}, but {e}", format_grist(cost)))
+}
+
+/// Whether every grist type in `grist` has at least as much in `total`
+fn covers(total: &GristTotals, grist: &GristTotals) -> bool {
+    grist
+        .iter()
+        .all(|(grist, amount)| total.get(grist).copied().unwrap_or_default() >= *amount)
+}
+
+impl CostConstraint {
+    /// Whether the costs meet the constraint
+    pub fn is_met(&self, item: &GristTotals, expression: &GristTotals) -> bool {
+        match self.relation {
+            Relation::Equal => item == expression,
+            Relation::AtLeast => covers(item, expression),
+            Relation::AtMost => covers(expression, item),
+        }
+    }
+}
+
+/// Reads constraints one per line, skipping blank lines and comments starting with `#`
+pub fn parse_constraints(text: &str) -> Result<Vec<CostConstraint>, String> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(i, line)| line.parse().map_err(|e| format!("Line {}: {e}", i + 1)))
+        .collect()
+}
+
+/// Works out costs for every item on the left of a constraint
+///
+/// Costs are found from constraints whose other side is known, pinned costs first, then going back from a known
+/// item to the one unknown item making it up, then the cheapest cost meeting every `>=` constraint. Items only on
+/// the right side use their existing cost, and are worked out the same way if they don't have one
+pub fn solve_costs(constraints: &[CostConstraint], existing: impl Fn(&str) -> Option<GristTotals>) -> CostSolution {
+    let targets: BTreeSet<&str> = constraints.iter().map(|constraint| constraint.item.as_str()).collect();
+    let mut costs: BTreeMap<String, GristTotals> = BTreeMap::new();
+    let mut problems: BTreeMap<usize, String> = BTreeMap::new();
+    let lookup = |costs: &BTreeMap<String, GristTotals>, item: &str| {
+        costs
+            .get(item)
+            .cloned()
+            .or_else(|| if targets.contains(item) { None } else { existing(item) })
+    };
+    loop {
+        let mut changed = false;
+        for (i, constraint) in constraints.iter().enumerate() {
+            if constraint.relation != Relation::Equal || problems.contains_key(&i) {
+                continue;
+            }
+            let Some(item_cost) = costs.get(&constraint.item).cloned() else {
+                let cost = evaluate(&constraint.expression, |item| lookup(&costs, item));
+                match cost.map(|cost| cost.and_then(|cost| check_amounts(&constraint.item, &cost).map(|_| cost))) {
+                    Some(Ok(cost)) => {
+                        costs.insert(constraint.item.clone(), cost);
+                        changed = true;
+                    }
+                    Some(Err(e)) => {
+                        problems.insert(i, e);
+                    }
+                    None => {}
+                }
+                continue;
+            };
+            //Going back from the item to the one unknown item in the expression
+            let unknown: BTreeSet<&str> = constraint
+                .expression
+                .items()
+                .into_iter()
+                .filter(|item| lookup(&costs, item).is_none())
+                .collect();
+            let [unknown] = unknown.into_iter().collect::<Vec<_>>()[..] else {
+                continue;
+            };
+            let Some(count) = constraint.expression.coefficient(unknown).filter(|count| *count != 0.0) else {
+                let reason = format!("{unknown} can't be worked back to, since it isn't just added or multiplied");
+                problems.insert(i, reason);
+                continue;
+            };
+            let rest = constraint.expression.evaluate(None, &|item| {
+                if item == unknown {
+                    Some(GristTotals::new())
+                } else {
+                    lookup(&costs, item)
+                }
+            });
+            let rest = match rest {
+                Ok(rest) => rest,
+                Err(e) => {
+                    problems.insert(i, e);
+                    continue;
+                }
+            };
+            let mut remaining = item_cost;
+            for (grist, amount) in rest {
+                *remaining.entry(grist).or_default() -= amount;
+            }
+            let amounts: BTreeMap<String, f64> = remaining
+                .iter()
+                .map(|(grist, amount)| (grist.clone(), *amount as f64 / count))
+                .collect();
+            if amounts.values().all(|amount| *amount >= 0.0 && is_whole(*amount)) {
+                let cost = amounts
+                    .into_iter()
+                    .filter(|(_, amount)| *amount != 0.0)
+                    .map(|(grist, amount)| (grist, amount.round() as i64))
+                    .collect();
+                costs.insert(unknown.to_string(), cost);
+                changed = true;
+            } else {
+                let reason = format!(
+                    "{unknown} would have to cost {} divided by {count}, which isn't a whole, positive amount",
+                    format_grist(&remaining)
+                );
+                problems.insert(i, reason);
+            }
+        }
+        if changed {
+            continue;
+        }
+        //Only once nothing else can be worked out, so lower bounds don't take the place of exact costs
+        for item in &targets {
+            if costs.contains_key(*item) {
+                continue;
+            }
+            let mut bounds = vec![];
+            let mut complete = true;
+            for (i, constraint) in constraints.iter().enumerate() {
+                if constraint.item != *item || constraint.relation != Relation::AtLeast || problems.contains_key(&i) {
+                    continue;
+                }
+                match evaluate(&constraint.expression, |item| lookup(&costs, item)) {
+                    Some(Ok(bound)) => bounds.push(bound),
+                    Some(Err(e)) => {
+                        problems.insert(i, e);
+                    }
+                    None => complete = false,
+                }
+            }
+            if !complete || bounds.is_empty() {
+                continue;
+            }
+            //Nothing is also at least a negative amount
+            let mut cost = GristTotals::new();
+            for bound in bounds {
+                for (grist, amount) in bound {
+                    let current = cost.entry(grist).or_default();
+                    *current = (*current).max(amount);
+                }
+            }
+            cost.retain(|_, amount| *amount > 0);
+            if let Err(e) = check_amounts(item, &cost) {
+                for (i, constraint) in constraints.iter().enumerate() {
+                    if constraint.item == *item && constraint.relation == Relation::AtLeast {
+                        problems.entry(i).or_insert_with(|| e.clone());
+                    }
+                }
+                continue;
+            }
+            costs.insert(item.to_string(), cost);
+            changed = true;
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    for (i, constraint) in constraints.iter().enumerate() {
+        if problems.contains_key(&i) {
+            continue;
+        }
+        match evaluate(&constraint.expression, |item| lookup(&costs, item)) {
+            Some(Ok(expression_cost)) => {
+                let Some(item_cost) = costs.get(&constraint.item) else {
+                    continue;
+                };
+                if !constraint.is_met(item_cost, &expression_cost) {
+                    let reason = format!(
+                        "{} costs {} but the other side is {}",
+                        constraint.item,
+                        format_grist(item_cost),
+                        format_grist(&expression_cost)
+                    );
+                    problems.insert(i, reason);
+                }
+            }
+            Some(Err(e)) => {
+                problems.insert(i, e);
+            }
+            None => {
+                let missing: Vec<&str> = constraint
+                    .expression
+                    .items()
+                    .into_iter()
+                    .filter(|item| lookup(&costs, item).is_none())
+                    .collect();
+                problems.insert(i, format!("no cost for {}", missing.join(", ")));
+            }
+        }
+    }
+    CostSolution {
+        unsolved: targets
+            .into_iter()
+            .filter(|item| !costs.contains_key(*item))
+            .map(str::to_string)
+            .collect(),
+        costs,
+        problems: problems
+            .into_iter()
+            .map(|(i, reason)| ConstraintProblem {
+                constraint: constraints[i].clone(),
+                reason,
+            })
+            .collect(),
+    }
+}
+
+impl CostSolution {
+    /// A grist cost recipe for every solved item
+    pub fn recipes(&self) -> Vec<GristCostRecipe> {
+        self.costs
+            .iter()
+            .map(|(item, grist)| GristCostRecipe {
+                priority: Some(GENERATED_PRIORITY),
+                ingredient: Ingredient::Item(item.clone()),
+                grist_cost: grist
+                    .iter()
+                    //Costs are only solved with amounts a recipe can hold
+                    .map(|(grist, amount)| (grist.clone(), *amount as i32))
+                    .collect(),
+                conditions: Default::default(),
+            })
+            .collect()
+    }
+}
+
+impl FromStr for CostConstraint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (item, relation, expression) = if let Some((item, expression)) = s.split_once(">=") {
+            (item, Relation::AtLeast, expression)
+        } else if let Some((item, expression)) = s.split_once("<=") {
+            (item, Relation::AtMost, expression)
+        } else if let Some((item, expression)) = s.split_once('=') {
+            (item, Relation::Equal, expression)
+        } else {
+            return Err(format!("Expected =, >= or <= in \"{s}\""));
+        };
+        let item = item.trim();
+        if item.is_empty() || item.contains(char::is_whitespace) {
+            return Err(format!("Expected an item before {relation} in \"{s}\""));
+        }
+        Ok(CostConstraint {
+            item: item.to_string(),
+            relation,
+            expression: expression.parse()?,
+        })
+    }
+}
+
+impl Display for Relation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Relation::Equal => write!(f, "="),
+            Relation::AtLeast => write!(f, ">="),
+            Relation::AtMost => write!(f, "<="),
+        }
+    }
+}
+
+impl Display for CostConstraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.item, self.relation, self.expression)
+    }
+}
+
+impl Display for ConstraintProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.constraint, self.reason)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve(lines: &[&str]) -> CostSolution {
+        solve_costs(&parse_constraints(&lines.join("\n")).unwrap(), |_| None)
+    }
+
+    fn grist(amounts: &[(&str, i64)]) -> GristTotals {
+        amounts.iter().map(|(grist, amount)| (format!("minestuck:{grist}"), *amount)).collect()
+    }
+
+    #[test]
+    fn pinned_and_multiplied() {
+        let solution = solve(&["a:ingot = build:2 + rust:6", "a:block = 9 * a:ingot"]);
+        assert!(solution.problems.is_empty());
+        assert_eq!(solution.costs["a:block"], grist(&[("build", 18), ("rust", 54)]));
+    }
+
+    #[test]
+    fn back_solves() {
+        let solution = solve(&[
+            "a:ingot = 9 * a:nugget",
+            "a:ingot = build:18 + rust:9",
+            "a:half = a:whole / 2 + build:1",
+            "a:half = build:4",
+        ]);
+        assert!(solution.problems.is_empty());
+        assert_eq!(solution.costs["a:nugget"], grist(&[("build", 2), ("rust", 1)]));
+        assert_eq!(solution.costs["a:whole"], grist(&[("build", 6)]));
+    }
+
+    #[test]
+    fn back_solve_not_dividing_evenly() {
+        let solution = solve(&["a:ingot = 9 * a:nugget", "a:ingot = build:10"]);
+        assert_eq!(solution.problems.len(), 1);
+        assert!(solution.problems[0].reason.contains("isn't a whole, positive amount"));
+        assert!(!solution.costs.contains_key("a:nugget"));
+    }
+
+    #[test]
+    fn negative_cost_is_a_problem() {
+        let solution = solve(&["a:x = a:y - build:10", "a:y = build:5"]);
+        assert_eq!(solution.problems.len(), 1);
+        assert!(solution.problems[0].reason.contains("less than nothing"));
+        assert!(!solution.costs.contains_key("a:x"));
+        assert!(solution.recipes().iter().all(|recipe| recipe.grist_cost.values().all(|amount| *amount >= 0)));
+    }
+
+    #[test]
+    fn lower_bounds() {
+        let solution = solve(&[
+            "a:pick >= 3 * a:ingot + build:1",
+            "a:pick >= rust:20",
+            "a:ingot = build:2 + rust:6",
+            "a:free >= a:ingot - build:5",
+        ]);
+        assert!(solution.problems.is_empty());
+        assert_eq!(solution.costs["a:pick"], grist(&[("build", 7), ("rust", 20)]));
+        assert_eq!(solution.costs["a:free"], grist(&[("rust", 6)]));
+    }
+}
@@ -0,0 +1,295 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Datapack, Loader, MCMeta, Pack, Recipe, ALWAYS_LOADED};
+
+/// How [`Datapack::split`] divides a pack
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SplitSettings {
+    /// The pack for everything that doesn't need a mod, and the only one keeping the filter from pack.mcmeta
+    pub shared_pack: String,
+    pub cross_mod: CrossModRecipes,
+}
+
+impl Default for SplitSettings {
+    fn default() -> Self {
+        Self {
+            shared_pack: "shared".to_string(),
+            cross_mod: CrossModRecipes::Shared,
+        }
+    }
+}
+
+/// Where recipes using items from mods in different packs go
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrossModRecipes {
+    /// In the shared pack
+    #[default]
+    Shared,
+    /// In the pack of the combination's output or the grist cost's item, only loaded when every mod used is
+    Conditions,
+}
+
+/// Reads a json object giving the pack for each namespace
+pub fn load_pack_mapping<P>(path: P) -> Result<BTreeMap<String, String>, String>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let json = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {path:?}, {e}"))?;
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse {path:?}, {e}"))
+}
+
+fn namespace(id: &str) -> Option<&str> {
+    id.split_once(':')
+        .map(|(namespace, _)| namespace)
+        .filter(|namespace| !ALWAYS_LOADED.contains(namespace))
+}
+
+/// The pack for a mod, named after it unless the mapping says otherwise
+fn pack_name(mapping: &BTreeMap<String, String>, namespace: &str) -> String {
+    mapping.get(namespace).cloned().unwrap_or_else(|| namespace.to_string())
+}
+
+impl Datapack {
+    /// Splits the pack into one for each mod, by the namespace of the items and grist types things use
+    ///
+    /// Namespaces can be mapped to pack names, several going to the same pack. Anything that only uses
+    /// Minecraft and Minestuck goes to the shared pack, as do recipes using mods in different packs unless they're
+    /// to go in the pack of the item they're for. Recipes needing mods get load conditions for them wherever they go
+    /// other than their own mod's pack. Tags are split by entry, since Minecraft adds together tags with the same id
+    pub fn split(
+        &self,
+        mapping: &BTreeMap<String, String>,
+        settings: &SplitSettings,
+        loader: Loader,
+    ) -> BTreeMap<String, Datapack> {
+        let mut packs: BTreeMap<String, Datapack> = BTreeMap::new();
+        let shared = settings.shared_pack.as_str();
+
+        for (location, recipe) in &self.recipes {
+            let mods = recipe.required_mods();
+            let mod_packs: BTreeSet<String> = mods.iter().map(|namespace| pack_name(mapping, namespace)).collect();
+            let mod_packs_count = mod_packs.len();
+            let mut recipe = recipe.clone();
+            let pack = match mod_packs_count {
+                0 => shared.to_string(),
+                1 => mod_packs.into_iter().next().unwrap(),
+                _ if settings.cross_mod == CrossModRecipes::Shared => shared.to_string(),
+                _ => {
+                    //The item the recipe is for decides the pack, the first mod if that's from Minecraft or Minestuck
+                    let item = match &recipe {
+                        Recipe::GristCost(_) => recipe.item_ids().first().copied(),
+                        Recipe::Combination(_) => recipe.item_ids().last().copied(),
+                    };
+                    let owner = item.and_then(namespace).or(mods.first().copied()).unwrap();
+                    pack_name(mapping, owner)
+                }
+            };
+            //The shared pack is loaded without any mods, and recipes for items of mods in different packs are only
+            //loaded with all of them
+            if !mods.is_empty() && (pack == shared || mod_packs_count > 1) {
+                let conditions = recipe.conditions_mut();
+                for mod_id in &mods {
+                    conditions.require_mod(mod_id);
+                }
+                conditions.loader = loader;
+            }
+            packs.entry(pack).or_default().recipes.insert(location.clone(), recipe);
+        }
+
+        for (id, grist_type) in &self.grist_types {
+            let pack = namespace(id).map_or(shared.to_string(), |namespace| pack_name(mapping, namespace));
+            packs.entry(pack).or_default().grist_types.insert(id.clone(), grist_type.clone());
+        }
+
+        for (id, tag) in &self.item_tags {
+            //A tag replacing earlier ones would only keep one pack's entries
+            if tag.replace || tag.values.is_empty() {
+                packs.entry(shared.to_string()).or_default().item_tags.insert(id.clone(), tag.clone());
+                continue;
+            }
+            for entry in &tag.values {
+                let pack = match namespace(&entry.id) {
+                    Some(namespace) if !entry.tag => pack_name(mapping, namespace),
+                    _ => shared.to_string(),
+                };
+                let split_tag = packs.entry(pack).or_default().item_tags.entry(id.clone()).or_default();
+                split_tag.values.push(entry.clone());
+            }
+        }
+
+        //Hiding recipes from packs loaded before is the same whichever mods are installed
+        if self.mcmeta.filter.is_some() {
+            packs.entry(shared.to_string()).or_default();
+        }
+        for (name, pack) in &mut packs {
+            pack.mcmeta = MCMeta {
+                pack: Pack {
+                    description: format!("{} ({name})", self.mcmeta.pack.description),
+                    ..self.mcmeta.pack.clone()
+                },
+                filter: self.mcmeta.filter.clone().filter(|_| name == shared),
+                extra: self.mcmeta.extra.clone(),
+            };
+        }
+        packs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        CombinationMode, CombinationRecipe, Condition, GristCostRecipe, GristType, Ingredient, ResultItem, Tag,
+        TagEntry,
+    };
+
+    fn grist_cost(item: &str, grist: &str) -> Recipe {
+        GristCostRecipe {
+            priority: None,
+            ingredient: Ingredient::Item(item.to_string()),
+            grist_cost: BTreeMap::from([(grist.to_string(), 1)]),
+            conditions: Default::default(),
+        }
+        .into()
+    }
+
+    fn combination(input1: &str, input2: &str, output: &str) -> Recipe {
+        Recipe::Combination(CombinationRecipe {
+            input1: Ingredient::Item(input1.to_string()),
+            input2: Ingredient::Item(input2.to_string()),
+            mode: CombinationMode::And,
+            output: ResultItem::Item(output.to_string()),
+            conditions: Default::default(),
+        })
+    }
+
+    fn datapack(recipes: &[Recipe]) -> Datapack {
+        let mut datapack = Datapack::new();
+        for (i, recipe) in recipes.iter().enumerate() {
+            datapack.recipes.insert(format!("recipe{i}"), recipe.clone());
+        }
+        datapack
+    }
+
+    /// The pack each recipe went to and the mods its conditions require
+    fn placement(packs: &BTreeMap<String, Datapack>) -> BTreeMap<String, (String, Vec<String>)> {
+        packs
+            .iter()
+            .flat_map(|(name, pack)| {
+                pack.recipes.iter().map(move |(location, recipe)| {
+                    let mods = recipe
+                        .conditions()
+                        .conditions
+                        .iter()
+                        .filter_map(|condition| match condition {
+                            Condition::ModLoaded(mod_id) => Some(mod_id.clone()),
+                            _ => None,
+                        })
+                        .collect();
+                    (location.clone(), (name.clone(), mods))
+                })
+            })
+            .collect()
+    }
+
+    fn placed(pack: &str, mods: &[&str]) -> (String, Vec<String>) {
+        (pack.to_string(), mods.iter().map(|mod_id| mod_id.to_string()).collect())
+    }
+
+    #[test]
+    fn by_namespace_and_mapping() {
+        let datapack = datapack(&[
+            grist_cost("minecraft:stone", "minestuck:build"),
+            grist_cost("create:cog", "minestuck:build"),
+            grist_cost("mekanism:ingot", "minestuck:build"),
+            grist_cost("minecraft:dirt", "create:grist"),
+        ]);
+        let mapping = BTreeMap::from([("mekanism".to_string(), "tech".to_string())]);
+        let packs = datapack.split(&mapping, &SplitSettings::default(), Loader::Forge);
+        assert_eq!(packs.keys().collect::<Vec<_>>(), ["create", "shared", "tech"]);
+        let placement = placement(&packs);
+        assert_eq!(placement["recipe0"], placed("shared", &[]));
+        assert_eq!(placement["recipe1"], placed("create", &[]));
+        assert_eq!(placement["recipe2"], placed("tech", &[]));
+        //Grist types decide the pack too
+        assert_eq!(placement["recipe3"], placed("create", &[]));
+    }
+
+    #[test]
+    fn cross_mod_in_the_shared_pack() {
+        let datapack = datapack(&[combination("create:cog", "mekanism:ingot", "minecraft:stone")]);
+        let packs = datapack.split(&BTreeMap::new(), &SplitSettings::default(), Loader::Forge);
+        assert_eq!(placement(&packs)["recipe0"], placed("shared", &["create", "mekanism"]));
+    }
+
+    #[test]
+    fn cross_mod_with_conditions() {
+        let datapack = datapack(&[
+            combination("create:cog", "mekanism:ingot", "mekanism:gear"),
+            combination("create:cog", "mekanism:ingot", "minecraft:stone"),
+        ]);
+        let settings = SplitSettings {
+            cross_mod: CrossModRecipes::Conditions,
+            ..Default::default()
+        };
+        let packs = datapack.split(&BTreeMap::new(), &settings, Loader::NeoForge);
+        let placement = placement(&packs);
+        //The output decides the pack, or the first mod if it's from Minecraft
+        assert_eq!(placement["recipe0"], placed("mekanism", &["create", "mekanism"]));
+        assert_eq!(placement["recipe1"], placed("create", &["create", "mekanism"]));
+        assert_eq!(packs["mekanism"].recipes["recipe0"].conditions().loader, Loader::NeoForge);
+    }
+
+    #[test]
+    fn mapped_to_the_shared_pack() {
+        let datapack = datapack(&[grist_cost("quark:slab", "minestuck:build")]);
+        let mapping = BTreeMap::from([("quark".to_string(), "shared".to_string())]);
+        let packs = datapack.split(&mapping, &SplitSettings::default(), Loader::Forge);
+        //The shared pack is installed without quark, so the recipe needs it loaded
+        assert_eq!(placement(&packs)["recipe0"], placed("shared", &["quark"]));
+    }
+
+    #[test]
+    fn tags_by_entry() {
+        let mut datapack = Datapack::new();
+        let entry = |id: &str, tag: bool| TagEntry {
+            id: id.to_string(),
+            tag,
+            required: true,
+        };
+        datapack.item_tags.insert(
+            "minestuck:metals".to_string(),
+            Tag {
+                replace: false,
+                values: vec![entry("create:zinc", false), entry("minecraft:iron_ingot", false), entry("c:ingots", true)],
+            },
+        );
+        datapack.item_tags.insert(
+            "minestuck:replaced".to_string(),
+            Tag {
+                replace: true,
+                values: vec![entry("create:zinc", false)],
+            },
+        );
+        datapack.grist_types.insert("create:zinc".to_string(), GristType::default());
+        let packs = datapack.split(&BTreeMap::new(), &SplitSettings::default(), Loader::Forge);
+        let values = |pack: &str, tag: &str| packs[pack].item_tags[tag].values.clone();
+        assert_eq!(values("create", "minestuck:metals"), [entry("create:zinc", false)]);
+        //Tag entries stay shared, since what's in them isn't known
+        assert_eq!(
+            values("shared", "minestuck:metals"),
+            [entry("minecraft:iron_ingot", false), entry("c:ingots", true)]
+        );
+        //Only one pack's version of a replacing tag would be kept
+        assert_eq!(values("shared", "minestuck:replaced"), [entry("create:zinc", false)]);
+        assert!(packs["create"].grist_types.contains_key("create:zinc"));
+    }
+}
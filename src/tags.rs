@@ -0,0 +1,335 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    fs::File,
+    io::Read,
+    path::Path,
+    sync::OnceLock,
+};
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Value};
+
+use crate::{Datapack, Ingredient, ItemRegistry, Recipe};
+
+/// An item tag file, `data/<namespace>/tags/items/<path>.json`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tag {
+    /// Replaces the tag from packs loaded before this one instead of adding to it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub replace: bool,
+    pub values: Vec<TagEntry>,
+}
+
+/// An item or, if `tag` is set, every item in another tag
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TagEntry {
+    pub id: String,
+    pub tag: bool,
+    /// If false the entry is skipped when it doesn't exist instead of the whole tag failing to load
+    pub required: bool,
+}
+
+impl TagEntry {
+    /// Reads `modid:item`, `#modid:tag`, either of which can start with `?` to make it optional
+    ///
+    /// The `?` is only a shorthand for csv, tag files write optional entries as `{"id": ..., "required": false}`
+    pub fn parse(s: &str) -> TagEntry {
+        let s = s.trim();
+        let (s, required) = match s.strip_prefix('?') {
+            Some(s) => (s, false),
+            None => (s, true),
+        };
+        let (id, tag) = match s.strip_prefix('#') {
+            Some(id) => (id, true),
+            None => (s, false),
+        };
+        TagEntry {
+            id: id.to_string(),
+            tag,
+            required,
+        }
+    }
+}
+
+impl Tag {
+    /// Location in a datapack of the file for the item tag with this id, without the .json extension
+    pub fn location(id: &str) -> Option<String> {
+        let (namespace, path) = id.split_once(':')?;
+        Some(format!("data/{namespace}/tags/items/{path}"))
+    }
+
+    /// The item tag id a file in a datapack is for, the reverse of [`Tag::location`]
+    pub fn id(location: &str) -> Option<String> {
+        let (namespace, rest) = location.strip_prefix("data/")?.split_once('/')?;
+        let path = rest
+            .strip_prefix("tags/items/")
+            .or_else(|| rest.strip_prefix("tags/item/"))?;
+        Some(format!("{namespace}:{path}"))
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.values
+            .iter()
+            .all(|entry| crate::validate_resource_location(&entry.id))
+    }
+}
+
+/// Item tags from every pack and mod combined the way Minecraft does, which can be resolved to the items in them
+#[derive(Debug, Clone, Default)]
+pub struct ItemTags {
+    tags: BTreeMap<String, Vec<TagEntry>>,
+    /// The tags each item is in, worked out the first time it's needed after a tag is added
+    containing: OnceLock<BTreeMap<String, Vec<String>>>,
+}
+
+/// Why a tag couldn't be resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagError {
+    Missing(String),
+    Cycle(Vec<String>),
+}
+
+impl ItemTags {
+    pub fn new() -> ItemTags {
+        Default::default()
+    }
+
+    /// Adds a tag file on top of whatever has been added before with the same id
+    pub fn add(&mut self, id: &str, tag: &Tag) {
+        self.containing = OnceLock::new();
+        let entries = self.tags.entry(id.to_string()).or_default();
+        if tag.replace {
+            entries.clear();
+        }
+        for entry in &tag.values {
+            if !entries.contains(entry) {
+                entries.push(entry.clone());
+            }
+        }
+    }
+
+    pub fn add_datapack(&mut self, datapack: &Datapack) {
+        for (id, tag) in &datapack.item_tags {
+            self.add(id, tag);
+        }
+    }
+
+    /// Adds tags from a datapack directory, or a mod jar or zipped datapack
+    pub fn load<P>(&mut self, path: P) -> std::io::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        if path.is_dir() {
+            self.add_datapack(&Datapack::load(path));
+            Ok(())
+        } else {
+            self.load_jar(path)
+        }
+    }
+
+    /// Adds every item tag in a mod jar or zipped datapack
+    pub fn load_jar<P>(&mut self, path: P) -> std::io::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+        let names: Vec<String> = archive.file_names().map(str::to_string).collect();
+        for name in names {
+            let Some(id) = name.strip_suffix(".json").and_then(Tag::id) else {
+                continue;
+            };
+            let mut json = String::new();
+            archive.by_name(&name)?.read_to_string(&mut json)?;
+            match serde_json::from_str::<Tag>(&json) {
+                Ok(tag) => self.add(&id, &tag),
+                Err(e) => eprintln!("Failed to parse tag {name}, {e}"),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.tags.contains_key(id)
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.tags.keys().map(String::as_str)
+    }
+
+    /// Every item in a tag, including ones in tags inside it
+    pub fn resolve(&self, id: &str) -> Result<BTreeSet<String>, TagError> {
+        let mut items = BTreeSet::new();
+        self.resolve_into(id, &mut vec![], &mut items)?;
+        Ok(items)
+    }
+
+    fn resolve_into(&self, id: &str, path: &mut Vec<String>, items: &mut BTreeSet<String>) -> Result<(), TagError> {
+        if let Some(start) = path.iter().position(|tag| tag == id) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(id.to_string());
+            return Err(TagError::Cycle(cycle));
+        }
+        let entries = self.tags.get(id).ok_or_else(|| TagError::Missing(id.to_string()))?;
+        path.push(id.to_string());
+        for entry in entries {
+            if entry.tag {
+                match self.resolve_into(&entry.id, path, items) {
+                    Err(TagError::Missing(_)) if !entry.required => {}
+                    result => result?,
+                }
+            } else {
+                items.insert(entry.id.clone());
+            }
+        }
+        path.pop();
+        Ok(())
+    }
+
+    /// Every item an ingredient matches, with tags resolved
+    pub fn ingredient_items(&self, ingredient: &Ingredient) -> Result<BTreeSet<String>, TagError> {
+        let mut items: BTreeSet<String> = ingredient.item_ids().into_iter().map(str::to_string).collect();
+        for tag in ingredient.tag_ids() {
+            items.extend(self.resolve(tag)?);
+        }
+        Ok(items)
+    }
+
+    /// Every tag that has the item in it, directly or through another tag
+    pub fn tags_containing(&self, item: &str) -> Vec<&str> {
+        let containing = self.containing.get_or_init(|| {
+            let mut containing: BTreeMap<String, Vec<String>> = BTreeMap::new();
+            for id in self.ids() {
+                for item in self.resolve(id).unwrap_or_default() {
+                    containing.entry(item).or_default().push(id.to_string());
+                }
+            }
+            containing
+        });
+        containing.get(item).into_iter().flatten().map(String::as_str).collect()
+    }
+
+    /// Problems with tags a recipe at location uses, tags that don't exist or can't be resolved
+    pub fn check(&self, location: &str, recipe: &Recipe) -> Vec<String> {
+        let tags: Vec<&str> = match recipe {
+            Recipe::GristCost(recipe) => recipe.ingredient.tag_ids(),
+            Recipe::Combination(recipe) => {
+                let mut tags = recipe.input1.tag_ids();
+                tags.extend(recipe.input2.tag_ids());
+                tags
+            }
+        };
+        tags.into_iter()
+            .filter_map(|tag| self.resolve(tag).err())
+            .map(|e| format!("{e} in {location}"))
+            .collect()
+    }
+
+    /// Required items in tags that aren't in the registry, which stop the tag from loading
+    pub fn unknown_items(&self, registry: &ItemRegistry) -> Vec<(&str, &str)> {
+        if registry.is_empty() {
+            return vec![];
+        }
+        self.tags
+            .iter()
+            .flat_map(|(id, entries)| entries.iter().map(move |entry| (id.as_str(), entry)))
+            .filter(|(_, entry)| !entry.tag && entry.required && !registry.contains(&entry.id))
+            .map(|(id, entry)| (id, entry.id.as_str()))
+            .collect()
+    }
+}
+
+impl Display for TagError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TagError::Missing(id) => write!(f, "Unknown tag #{id}"),
+            TagError::Cycle(cycle) => write!(f, "Tags contain each other, #{}", cycle.join(" -> #")),
+        }
+    }
+}
+
+impl Serialize for TagEntry {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let id = if self.tag {
+            format!("#{}", self.id)
+        } else {
+            self.id.clone()
+        };
+        if self.required {
+            id.serialize(serializer)
+        } else {
+            json!({ "id": id, "required": false }).serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TagEntry {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        //Minecraft doesn't know the csv shorthand for optional entries
+        let parse = |id: &str| {
+            if let Some(optional) = id.strip_prefix('?') {
+                Err(D::Error::custom(format!(
+                    "Tag entry {id} can't start with ?, write it as {{\"id\": \"{optional}\", \"required\": false}}"
+                )))
+            } else {
+                Ok(TagEntry::parse(id))
+            }
+        };
+        match Value::deserialize(deserializer)? {
+            Value::String(id) => parse(&id),
+            Value::Object(object) => {
+                let id = object
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| D::Error::custom("Tag entry needs an id"))?;
+                let required = object.get("required").and_then(Value::as_bool).unwrap_or(true);
+                Ok(TagEntry {
+                    required,
+                    ..parse(id)?
+                })
+            }
+            other => Err(D::Error::custom(format!("Tag entry should be an id or object: {other}"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(values: &[&str]) -> Tag {
+        Tag {
+            replace: false,
+            values: values.iter().map(|value| TagEntry::parse(value)).collect(),
+        }
+    }
+
+    #[test]
+    fn optional_entries() {
+        assert!(!TagEntry::parse("?test:ruby").required);
+        let json = serde_json::to_value(tag(&["?#test:gems", "test:ruby"])).unwrap();
+        assert_eq!(json, json!({ "values": [{ "id": "#test:gems", "required": false }, "test:ruby"] }));
+        assert_eq!(serde_json::from_value::<Tag>(json).unwrap(), tag(&["?#test:gems", "test:ruby"]));
+        assert!(serde_json::from_value::<Tag>(json!({ "values": ["?test:ruby"] })).is_err());
+        assert!(serde_json::from_value::<Tag>(json!({ "values": [{ "id": "?test:ruby" }] })).is_err());
+    }
+
+    #[test]
+    fn tags_containing() {
+        let mut tags = ItemTags::new();
+        tags.add("test:gems", &tag(&["test:ruby"]));
+        tags.add("test:shiny", &tag(&["#test:gems", "test:gold"]));
+        assert_eq!(tags.tags_containing("test:ruby"), ["test:gems", "test:shiny"]);
+        assert!(tags.tags_containing("test:stone").is_empty());
+        //Adding a tag after looking one up isn't missed
+        tags.add("test:red", &tag(&["test:ruby"]));
+        assert_eq!(tags.tags_containing("test:ruby"), ["test:gems", "test:red", "test:shiny"]);
+    }
+}